@@ -0,0 +1,427 @@
+//! Accepted baselines of the benchmark results, similar to snapshot testing: a
+//! run is accepted as the baseline, later runs are checked against it.
+
+use crate::tables::{display_date, BaselineCheckTable, BaselineCheckTableEntry, DeviationStatus};
+use crate::{ExtrinsicCollection, Metric, RoundBy};
+use failure::Error;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Baseline file if none is specified.
+pub const DEFAULT_BASELINE_FILE: &str = "bench-baseline.json";
+
+/// Version of the baseline format, increased on incompatible changes.
+const BASELINE_VERSION: u32 = 1;
+
+#[allow(non_local_definitions)]
+mod error {
+    #[derive(Debug, Fail)]
+    pub(super) enum BaselineError {
+        #[fail(display = "collection does not contain any results")]
+        EmptyResults,
+        #[fail(display = "baseline has unsupported version {}", 0)]
+        UnsupportedVersion(u32),
+        #[fail(
+            display = "invalid expiry date \"{}\" of {}, expected YYYY-MM-DD",
+            0, 1
+        )]
+        InvalidExpiryDate(String, String),
+    }
+}
+
+use error::BaselineError::*;
+
+/// Average times of each extrinsic and of each of its steps.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BaselineSnapshot {
+    version: u32,
+    pub metric: Metric,
+    /// Overhead extrinsic which was subtracted, applied again when checking.
+    pub overhead: Option<String>,
+    extrinsics: Vec<BaselineExtrinsic>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct BaselineExtrinsic {
+    pallet: String,
+    extrinsic: String,
+    average: f64,
+    steps: Vec<BaselineStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct BaselineStep {
+    input_vars: Vec<u64>,
+    average: f64,
+}
+
+/// Tolerances and accepted deviations as configured in a TOML file, e.g.
+///
+/// ```toml
+/// # Deviation (in %) of the average of an extrinsic which is ok (default: 10)
+/// tolerance = 10.0
+/// # Deviation (in %) of the average of a step which is ok (default: tolerance)
+/// step_tolerance = 20.0
+///
+/// [[accepted]]
+/// extrinsic = "balances.transfer"
+/// reason = "Additional storage read for the new lock"
+/// expires = "2021-06-01"
+/// # Largest accepted deviation (in %), any if not specified
+/// max_change = 15.0
+/// ```
+#[derive(Debug, Clone, PartialEq, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BaselineConfig {
+    pub tolerance: Option<f64>,
+    pub step_tolerance: Option<f64>,
+    #[serde(default)]
+    pub accepted: Vec<AcceptedDeviation>,
+}
+
+/// A known deviation of an extrinsic (and its steps) from the baseline.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AcceptedDeviation {
+    /// Extrinsic as `pallet.extrinsic`.
+    pub extrinsic: String,
+    pub reason: String,
+    /// Last day (`YYYY-MM-DD`) on which the deviation is accepted.
+    pub expires: String,
+    pub max_change: Option<f64>,
+}
+
+impl BaselineConfig {
+    /// Reads and parses the config file at the given path.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+    fn tolerance(&self) -> f64 {
+        self.tolerance.unwrap_or(10.0)
+    }
+    fn step_tolerance(&self) -> f64 {
+        self.step_tolerance.unwrap_or_else(|| self.tolerance())
+    }
+    /// Status of a deviation of the given extrinsic, where `today` is formatted as
+    /// `YYYY-MM-DD`.
+    fn status(
+        &self,
+        pallet: &str,
+        extrinsic: &str,
+        percentage: Option<f64>,
+        today: &str,
+    ) -> (DeviationStatus, Option<&str>) {
+        let id = format!("{}.{}", pallet, extrinsic);
+        let mut status = (DeviationStatus::Unexplained, None);
+
+        for accepted in self.accepted.iter().filter(|a| a.extrinsic == id) {
+            let within = match (accepted.max_change, percentage) {
+                (Some(max_change), Some(percentage)) => percentage.abs() <= max_change,
+                _ => true,
+            };
+            if !within {
+                continue;
+            }
+
+            // Dates formatted as `YYYY-MM-DD` are ordered like strings
+            if accepted.expires.as_str() >= today {
+                return (DeviationStatus::Accepted, Some(accepted.reason.as_str()));
+            }
+            status = (DeviationStatus::Expired, Some(accepted.reason.as_str()));
+        }
+
+        status
+    }
+}
+
+impl std::str::FromStr for BaselineConfig {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let config: BaselineConfig = toml::from_str(s)?;
+
+        let date = Regex::new(r"^\d{4}-\d{2}-\d{2}$").unwrap();
+        for accepted in &config.accepted {
+            if !date.is_match(&accepted.expires) {
+                return Err(InvalidExpiryDate(
+                    accepted.expires.clone(),
+                    accepted.extrinsic.clone(),
+                )
+                .into());
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+/// Today's date (UTC), formatted as `YYYY-MM-DD`.
+pub fn today() -> String {
+    display_date(
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0),
+    )
+}
+
+/// Deviation (in %) of `current` from `baseline`.
+fn percentage(baseline: f64, current: f64) -> f64 {
+    ((current - baseline) / baseline * 100.0).round_by(4)
+}
+
+impl BaselineSnapshot {
+    /// Creates the baseline of the collection, based on the given metric. The
+    /// overhead is only recorded, it must already be subtracted.
+    pub fn new(
+        collection: &ExtrinsicCollection,
+        metric: Metric,
+        overhead: Option<&str>,
+    ) -> Result<Self, Error> {
+        if collection.is_empty() {
+            return Err(EmptyResults.into());
+        }
+
+        let extrinsics = collection
+            .results
+            .iter()
+            .map(|result| BaselineExtrinsic {
+                pallet: result.pallet.clone(),
+                extrinsic: result.extrinsic.clone(),
+                average: result.average_time(metric).round_by(4),
+                steps: step_averages(collection, &result.pallet, &result.extrinsic, metric)
+                    .into_iter()
+                    .map(|(input_vars, average)| BaselineStep {
+                        input_vars,
+                        average,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(BaselineSnapshot {
+            version: BASELINE_VERSION,
+            metric,
+            overhead: overhead.map(|overhead| overhead.to_string()),
+            extrinsics,
+        })
+    }
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let snapshot: BaselineSnapshot = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if snapshot.version != BASELINE_VERSION {
+            return Err(UnsupportedVersion(snapshot.version).into());
+        }
+
+        Ok(snapshot)
+    }
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+    /// Checks the collection against the baseline and returns the deviations:
+    /// averages of extrinsics or steps which deviate by more than the tolerance,
+    /// as well as missing and new extrinsics or steps. `today` (`YYYY-MM-DD`)
+    /// decides whether the accepted deviations have expired.
+    pub fn check<'a>(
+        &'a self,
+        collection: &'a ExtrinsicCollection,
+        config: &'a BaselineConfig,
+        today: &str,
+    ) -> BaselineCheckTable<'a> {
+        let mut table = BaselineCheckTable::new();
+
+        let mut push = |pallet: &'a str,
+                        extrinsic: &'a str,
+                        input_vars: Option<&'a [u64]>,
+                        baseline_time: Option<f64>,
+                        current_time: Option<f64>| {
+            let percentage = match (baseline_time, current_time) {
+                (Some(baseline_time), Some(current_time)) => {
+                    Some(percentage(baseline_time, current_time))
+                }
+                _ => None,
+            };
+            let (status, reason) = config.status(pallet, extrinsic, percentage, today);
+
+            table.push(BaselineCheckTableEntry {
+                pallet,
+                extrinsic,
+                input_vars,
+                baseline_time,
+                current_time,
+                percentage,
+                status,
+                reason,
+            });
+        };
+
+        for baseline in &self.extrinsics {
+            let result = collection
+                .results
+                .iter()
+                .find(|r| r.pallet == baseline.pallet && r.extrinsic == baseline.extrinsic);
+            let result = match result {
+                Some(result) => result,
+                None => {
+                    push(
+                        &baseline.pallet,
+                        &baseline.extrinsic,
+                        None,
+                        Some(baseline.average),
+                        None,
+                    );
+                    continue;
+                }
+            };
+
+            let average = result.average_time(self.metric).round_by(4);
+            if percentage(baseline.average, average).abs() > config.tolerance() {
+                push(
+                    &baseline.pallet,
+                    &baseline.extrinsic,
+                    None,
+                    Some(baseline.average),
+                    Some(average),
+                );
+            }
+
+            let steps = step_averages(collection, &result.pallet, &result.extrinsic, self.metric);
+            for step in &baseline.steps {
+                match steps.get(&step.input_vars) {
+                    Some(&average)
+                        if percentage(step.average, average).abs() <= config.step_tolerance() => {}
+                    average => push(
+                        &baseline.pallet,
+                        &baseline.extrinsic,
+                        Some(&step.input_vars),
+                        Some(step.average),
+                        average.cloned(),
+                    ),
+                }
+            }
+
+            // Steps which are not part of the baseline
+            let mut new_steps: Vec<&[u64]> = result
+                .steps_repeats
+                .iter()
+                .map(|step| step.input_vars.as_slice())
+                .filter(|input_vars| baseline.steps.iter().all(|s| s.input_vars != *input_vars))
+                .collect();
+            new_steps.sort_unstable();
+            new_steps.dedup();
+
+            for input_vars in new_steps {
+                push(
+                    &result.pallet,
+                    &result.extrinsic,
+                    Some(input_vars),
+                    None,
+                    steps.get(input_vars).cloned(),
+                );
+            }
+        }
+
+        // Extrinsics which are not part of the baseline
+        for result in &collection.results {
+            if !self
+                .extrinsics
+                .iter()
+                .any(|b| b.pallet == result.pallet && b.extrinsic == result.extrinsic)
+            {
+                push(
+                    &result.pallet,
+                    &result.extrinsic,
+                    None,
+                    None,
+                    Some(result.average_time(self.metric).round_by(4)),
+                );
+            }
+        }
+
+        table
+    }
+}
+
+/// Average time of each step of the given extrinsic, by input variables.
+fn step_averages(
+    collection: &ExtrinsicCollection,
+    pallet: &str,
+    extrinsic: &str,
+    metric: Metric,
+) -> BTreeMap<Vec<u64>, f64> {
+    let mut steps: BTreeMap<Vec<u64>, (u64, usize)> = BTreeMap::new();
+    for result in collection
+        .results
+        .iter()
+        .filter(|r| r.pallet == pallet && r.extrinsic == extrinsic)
+    {
+        for step in &result.steps_repeats {
+            let entry = steps.entry(step.input_vars.clone()).or_default();
+            entry.0 += step.time(metric);
+            entry.1 += 1;
+        }
+    }
+
+    steps
+        .into_iter()
+        .map(|(input_vars, (total, count))| (input_vars, (total as f64 / count as f64).round_by(4)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status() {
+        let config: BaselineConfig = r#"
+            [[accepted]]
+            extrinsic = "balances.transfer"
+            reason = "new lock"
+            expires = "2021-06-01"
+            max_change = 15.0
+        "#
+        .parse()
+        .unwrap();
+
+        assert_eq!(
+            config.status("balances", "transfer", Some(12.0), "2021-06-01"),
+            (DeviationStatus::Accepted, Some("new lock"))
+        );
+        assert_eq!(
+            config.status("balances", "transfer", Some(-12.0), "2021-05-01"),
+            (DeviationStatus::Accepted, Some("new lock"))
+        );
+        assert_eq!(
+            config.status("balances", "transfer", Some(12.0), "2021-06-02"),
+            (DeviationStatus::Expired, Some("new lock"))
+        );
+        assert_eq!(
+            config.status("balances", "transfer", Some(16.0), "2021-05-01"),
+            (DeviationStatus::Unexplained, None)
+        );
+        assert_eq!(
+            config.status("balances", "set_balance", Some(12.0), "2021-05-01"),
+            (DeviationStatus::Unexplained, None)
+        );
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!("tolerances = 10.0".parse::<BaselineConfig>().is_err());
+        assert!(r#"
+            [[accepted]]
+            extrinsic = "balances.transfer"
+            reason = "new lock"
+            expires = "01.06.2021"
+        "#
+        .parse::<BaselineConfig>()
+        .is_err());
+
+        let config: BaselineConfig = "tolerance = 5.0".parse().unwrap();
+        assert_eq!(config.step_tolerance(), 5.0);
+    }
+}
@@ -0,0 +1,57 @@
+//! Projection of how many calls of an extrinsic fit into a block.
+
+/// Block limits used for the capacity projection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapacityConfig {
+    /// Execution time available per block, in nanoseconds.
+    pub block_time_nanos: f64,
+    /// Share of the block available to normal dispatches, e.g. `0.75` for 75%.
+    pub normal_ratio: f64,
+}
+
+impl Default for CapacityConfig {
+    fn default() -> Self {
+        CapacityConfig {
+            block_time_nanos: 2_000_000_000.0,
+            normal_ratio: 0.75,
+        }
+    }
+}
+
+impl CapacityConfig {
+    /// Creates the limits from a block weight limit instead of a block time, where
+    /// `weight_per_nanos` converts the weight into time (see `WeightConfig`).
+    pub fn from_block_weight(block_weight: f64, weight_per_nanos: f64, normal_ratio: f64) -> Self {
+        CapacityConfig {
+            block_time_nanos: block_weight / weight_per_nanos,
+            normal_ratio,
+        }
+    }
+    /// Time available to normal dispatches, in nanoseconds.
+    pub(crate) fn budget(&self) -> f64 {
+        self.block_time_nanos * self.normal_ratio
+    }
+    /// Amount of calls which take the given time (in nanoseconds) that fit into
+    /// a block. Times below one nanosecond, the resolution of the results, count
+    /// as one nanosecond.
+    pub(crate) fn calls(&self, time: f64) -> u64 {
+        (self.budget() / time.max(1.0)).floor() as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calls() {
+        let config = CapacityConfig::default();
+        assert_eq!(config.budget(), 1_500_000_000.0);
+        assert_eq!(config.calls(1_000_000.0), 1_500);
+        assert_eq!(config.calls(1_000_001.0), 1_499);
+        assert_eq!(config.calls(0.0), 1_500_000_000);
+
+        let config = CapacityConfig::from_block_weight(2_000_000_000_000.0, 1_000.0, 0.5);
+        assert_eq!(config.calls(1_000_000.0), 1_000);
+    }
+}
@@ -0,0 +1,254 @@
+//! Generation of Rust weight functions, based on a simple template.
+//!
+//! Templates contain placeholders, e.g. `{{pallet}}`, and sections which are
+//! repeated for each item of a list, e.g. `{{#pallets}} ... {{/pallets}}`.
+//! Within a section, the placeholders of the enclosing sections are available
+//! as well. See `DEFAULT_TEMPLATE` for the available placeholders.
+
+use super::tables::WeightTable;
+
+use std::collections::BTreeMap;
+
+use failure::Error;
+
+#[allow(non_local_definitions)]
+mod error {
+    #[derive(Debug, Fail)]
+    pub(super) enum TemplateError {
+        #[fail(display = "template section \"{}\" is not closed", 0)]
+        UnclosedSection(String),
+        #[fail(display = "template section \"{}\" is closed without being opened", 0)]
+        UnexpectedClose(String),
+        #[fail(display = "template placeholder \"{}\" is not closed", 0)]
+        UnclosedPlaceholder(String),
+        #[fail(display = "unknown template placeholder \"{}\"", 0)]
+        UnknownPlaceholder(String),
+    }
+}
+
+use error::TemplateError::*;
+
+/// The built-in template. Available placeholders:
+/// - `{{#pallets}}`: section for each pallet
+///   - `{{pallet}}`: name of the pallet
+///   - `{{#functions}}`: section for each extrinsic of the pallet
+///     - `{{extrinsic}}`: name of the extrinsic
+///     - `{{params}}`: function parameters, e.g. `u: u32, _e: u32`, where the
+///       components without weight are prefixed with `_`
+///     - `{{base_weight}}`: base weight
+///     - `{{#components}}`: section for each component of the extrinsic, except
+///       for the ones without weight
+///       - `{{component}}`: name of the component
+///       - `{{weight}}`: weight per unit of the component
+pub const DEFAULT_TEMPLATE: &str = "\
+//! Weights generated by bench-review. Do not edit manually.
+
+#![allow(unused_parens)]
+
+use frame_support::weights::Weight;
+{{#pallets}}
+pub mod {{pallet}} {
+    use super::Weight;
+{{#functions}}
+    pub fn {{extrinsic}}({{params}}) -> Weight {
+        ({{base_weight}} as Weight){{#components}}
+            .saturating_add(({{component}} as Weight).saturating_mul({{weight}})){{/components}}
+    }
+{{/functions}}}
+{{/pallets}}";
+
+/// A value which can be inserted into a template.
+#[derive(Debug)]
+enum Value {
+    Text(String),
+    List(Vec<Scope>),
+}
+
+type Scope = BTreeMap<&'static str, Value>;
+
+/// Parsed part of a template.
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Placeholder(String),
+    Section(String, Vec<Node>),
+}
+
+/// Renders the weight functions of all extrinsics in the table with the given
+/// template, grouped by pallet.
+pub fn render_weights(table: &WeightTable, template: &str) -> Result<String, Error> {
+    let mut pallets: BTreeMap<&str, Vec<Scope>> = BTreeMap::new();
+
+    for (pallet, extrinsic, base_weight, component_weights) in table.raw_list() {
+        let mut function = Scope::new();
+        function.insert("extrinsic", Value::Text(extrinsic.to_string()));
+        function.insert(
+            "params",
+            Value::Text(
+                component_weights
+                    .iter()
+                    .map(|(name, weight)| {
+                        // Unused parameters, their weight is not rendered
+                        if *weight == 0 {
+                            format!("_{}: u32", name)
+                        } else {
+                            format!("{}: u32", name)
+                        }
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            ),
+        );
+        function.insert("base_weight", Value::Text(base_weight.to_string()));
+        function.insert(
+            "components",
+            Value::List(
+                component_weights
+                    .iter()
+                    .filter(|(_, weight)| *weight > 0)
+                    .map(|(name, weight)| {
+                        let mut component = Scope::new();
+                        component.insert("component", Value::Text(name.to_string()));
+                        component.insert("weight", Value::Text(weight.to_string()));
+                        component
+                    })
+                    .collect(),
+            ),
+        );
+
+        pallets.entry(pallet).or_default().push(function);
+    }
+
+    let mut root = Scope::new();
+    root.insert(
+        "pallets",
+        Value::List(
+            pallets
+                .into_iter()
+                .map(|(pallet, functions)| {
+                    let mut scope = Scope::new();
+                    scope.insert("pallet", Value::Text(pallet.to_string()));
+                    scope.insert("functions", Value::List(functions));
+                    scope
+                })
+                .collect(),
+        ),
+    );
+
+    let (nodes, _) = parse(template, None)?;
+    let mut output = String::new();
+    render(&nodes, &mut vec![&root], &mut output)?;
+
+    Ok(output)
+}
+
+/// Parses the template into nodes, until the end of the given section (if any) is
+/// reached. Returns the nodes and the remaining, unparsed template.
+fn parse<'a>(mut template: &'a str, section: Option<&str>) -> Result<(Vec<Node>, &'a str), Error> {
+    let mut nodes = Vec::new();
+
+    while let Some(start) = template.find("{{") {
+        if start > 0 {
+            nodes.push(Node::Text(template[..start].to_string()));
+        }
+
+        let end = template[start..]
+            .find("}}")
+            .ok_or_else(|| UnclosedPlaceholder(template[start..].to_string()))?;
+        let tag = template[start + 2..start + end].trim();
+        template = &template[start + end + 2..];
+
+        if let Some(name) = tag.strip_prefix('#') {
+            let (children, rest) = parse(template, Some(name))?;
+            nodes.push(Node::Section(name.to_string(), children));
+            template = rest;
+        } else if let Some(name) = tag.strip_prefix('/') {
+            if section != Some(name) {
+                return Err(UnexpectedClose(name.to_string()).into());
+            }
+
+            return Ok((nodes, template));
+        } else {
+            nodes.push(Node::Placeholder(tag.to_string()));
+        }
+    }
+
+    if let Some(name) = section {
+        return Err(UnclosedSection(name.to_string()).into());
+    }
+
+    if !template.is_empty() {
+        nodes.push(Node::Text(template.to_string()));
+    }
+
+    Ok((nodes, ""))
+}
+
+/// Renders the nodes, where placeholders are looked up in the innermost scope first.
+fn render(nodes: &[Node], scopes: &mut Vec<&Scope>, output: &mut String) -> Result<(), Error> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Placeholder(name) => match lookup(scopes, name) {
+                Some(Value::Text(text)) => output.push_str(text),
+                _ => return Err(UnknownPlaceholder(name.clone()).into()),
+            },
+            Node::Section(name, children) => match lookup(scopes, name) {
+                Some(Value::List(items)) => {
+                    for item in items {
+                        scopes.push(item);
+                        render(children, scopes, output)?;
+                        scopes.pop();
+                    }
+                }
+                _ => return Err(UnknownPlaceholder(name.clone()).into()),
+            },
+        }
+    }
+
+    Ok(())
+}
+
+fn lookup<'a>(scopes: &[&'a Scope], name: &str) -> Option<&'a Value> {
+    scopes.iter().rev().find_map(|scope| scope.get(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(entries: Vec<(&'static str, Value)>) -> Scope {
+        entries.into_iter().collect()
+    }
+
+    #[test]
+    fn test_render() {
+        let root = scope(vec![
+            ("name", Value::Text("outer".to_string())),
+            (
+                "items",
+                Value::List(vec![
+                    scope(vec![("value", Value::Text("1".to_string()))]),
+                    scope(vec![("value", Value::Text("2".to_string()))]),
+                ]),
+            ),
+        ]);
+
+        let (nodes, _) =
+            parse("{{name}}:{{#items}} {{ value }}/{{name}}{{/items}}!", None).unwrap();
+        let mut output = String::new();
+        render(&nodes, &mut vec![&root], &mut output).unwrap();
+        assert_eq!(output, "outer: 1/outer 2/outer!");
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        assert!(parse("{{#items}} ...", None).is_err());
+        assert!(parse("... {{/items}}", None).is_err());
+        assert!(parse("{{#items}} {{/other}}", None).is_err());
+        assert!(parse("{{name", None).is_err());
+
+        let (nodes, _) = parse("{{unknown}}", None).unwrap();
+        assert!(render(&nodes, &mut vec![&Scope::new()], &mut String::new()).is_err());
+    }
+}
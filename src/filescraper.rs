@@ -6,50 +6,203 @@ use std::io::prelude::*;
 use std::iter::Iterator;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process::Command;
 
 use failure::Error;
 
-#[derive(Debug, Fail)]
-enum FileContentError {
-    #[fail(display = "Invalid document: {}", 0)]
-    InvalidDocument(String),
+/// Prefix of the paths which refer to results committed to git.
+const GIT_PREFIX: &str = "git:";
+
+#[allow(non_local_definitions)]
+mod error {
+    #[derive(Debug, Fail)]
+    pub(super) enum FileContentError {
+        #[fail(display = "Invalid document: {}", 0)]
+        InvalidDocument(String),
+        #[fail(display = "invalid git source \"{}\", expected git:<rev>:<path>", 0)]
+        InvalidGitSource(String),
+        #[fail(display = "`git {}` failed: {}", command, stderr)]
+        GitFailed { command: String, stderr: String },
+        #[fail(display = "no files found in {}", 0)]
+        EmptyGitSource(String),
+    }
 }
 
-use FileContentError::*;
+use error::FileContentError::*;
+
+/// 64 bit FNV-1a hash, identifies the content of a result file.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
 
 pub struct FileContent(pub(crate) (String, PathBuf));
 
 impl FileContent {
     pub fn parse(&self) -> Result<ExtrinsicResult, Error> {
-        let mut extrinsic_result = parser::parse_header(self)
+        let (mut extrinsic_result, layout) = parser::parse_header(self)
             .map_err(|_| InvalidDocument((self.0).1.to_string_lossy().to_string()))?;
-        let expected_len = extrinsic_result.input_var_names.len() + 2;
-        extrinsic_result.steps_repeats = parser::parse_body(self, expected_len)
+        extrinsic_result.steps_repeats = parser::parse_body(self, &layout)
             .map_err(|_| InvalidDocument((self.0).1.to_string_lossy().to_string()))?;
+        extrinsic_result.file_hash = fnv1a((self.0).0.as_bytes());
         Ok(extrinsic_result)
     }
 }
 
+/// Results committed to a git repository, read straight from the object
+/// database without a checkout. Specified as `git:<rev>:<path>`, e.g.
+/// `git:HEAD~1:benchmarks/results`, where the path is relative to the current
+/// directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GitSource {
+    /// Directory within the repository, `git` is run from there.
+    pub repo: PathBuf,
+    pub rev: String,
+    pub path: String,
+}
+
+impl GitSource {
+    pub fn new<P: AsRef<Path>>(repo: P, rev: &str, path: &str) -> Self {
+        GitSource {
+            repo: repo.as_ref().to_path_buf(),
+            rev: rev.to_string(),
+            path: path.to_string(),
+        }
+    }
+    /// Whether the path refers to results committed to git.
+    pub fn is_git_source(path: &str) -> bool {
+        path.starts_with(GIT_PREFIX)
+    }
+    /// Resolves the revision to the full hash of its commit.
+    pub fn commit(&self) -> Result<String, Error> {
+        let output = self.git(&["rev-parse", "--verify", &format!("{}^{{commit}}", self.rev)])?;
+        Ok(String::from_utf8_lossy(&output).trim().to_string())
+    }
+    /// Lists the files below the path at the given commit, relative to the root
+    /// of the repository.
+    fn list_files(&self, commit: &str) -> Result<Vec<String>, Error> {
+        let output = self.git(&[
+            "ls-tree",
+            "-r",
+            "-z",
+            "--name-only",
+            "--full-name",
+            commit,
+            "--",
+            &self.path,
+        ])?;
+
+        let files: Vec<String> = String::from_utf8_lossy(&output)
+            .split('\0')
+            .filter(|file| !file.is_empty())
+            .map(|file| file.to_string())
+            .collect();
+
+        if files.is_empty() {
+            return Err(EmptyGitSource(format!("{}{}:{}", GIT_PREFIX, self.rev, self.path)).into());
+        }
+
+        Ok(files)
+    }
+    /// Reads the file at the given commit. The path of the content is a virtual
+    /// one, formatted as `git:<commit>:<file>`.
+    fn read_file(&self, commit: &str, file: &str) -> Result<FileContent, Error> {
+        let output = self.git(&["cat-file", "blob", &format!("{}:{}", commit, file)])?;
+        Ok(FileContent((
+            String::from_utf8(output)?,
+            PathBuf::from(format!("{}{}:{}", GIT_PREFIX, commit, file)),
+        )))
+    }
+    fn git(&self, args: &[&str]) -> Result<Vec<u8>, Error> {
+        let output = Command::new("git")
+            .current_dir(&self.repo)
+            .args(args)
+            .output()?;
+
+        if !output.status.success() {
+            return Err(GitFailed {
+                command: args.join(" "),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            }
+            .into());
+        }
+
+        Ok(output.stdout)
+    }
+}
+
+impl std::str::FromStr for GitSource {
+    type Err = Error;
+
+    /// Parses `git:<rev>:<path>`, relative to the current directory.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidGitSource(s.to_string());
+
+        let rest = s.strip_prefix(GIT_PREFIX).ok_or_else(invalid)?;
+        let (rev, path) = rest.split_once(':').ok_or_else(invalid)?;
+        if rev.is_empty() {
+            return Err(invalid().into());
+        }
+
+        // An empty path refers to the whole repository
+        let path = if path.is_empty() { "." } else { path };
+
+        Ok(GitSource::new(".", rev, path))
+    }
+}
+
 pub struct FileScraper {
     files: Vec<PathBuf>,
     count: usize,
+    /// Source and resolved commit, if the files are read from git.
+    git: Option<(GitSource, String)>,
 }
 
 impl FileScraper {
     /// Recursively searches for all files within the specified `path`,
-    /// saving those internally.
+    /// saving those internally. Paths formatted as `git:<rev>:<path>` are read
+    /// from git, see `GitSource`.
     pub fn new<P: AsRef<Path>>(path: P) -> Result<FileScraper, Error> {
+        let path = path.as_ref();
+        if let Some(source) = path.to_str().filter(|p| GitSource::is_git_source(p)) {
+            return FileScraper::from_git(source.parse()?);
+        }
+
         Ok(FileScraper {
             files: find_files(path)?,
             count: 0,
+            git: None,
         })
     }
+    /// Lists all files within the path of the source at its revision.
+    pub fn from_git(source: GitSource) -> Result<FileScraper, Error> {
+        let commit = source.commit()?;
+        let files = source
+            .list_files(&commit)?
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+
+        Ok(FileScraper {
+            files,
+            count: 0,
+            git: Some((source, commit)),
+        })
+    }
+    /// Full hash of the commit the files are read from, if read from git.
+    pub fn commit(&self) -> Option<&str> {
+        self.git.as_ref().map(|(_, commit)| commit.as_str())
+    }
 }
 
 /// Searches for files insides the specified `path` and saves the full path of each
 /// file. If a directory is found, this function will repeat that same process for
 /// that subdirectory (recursion).
-fn find_files<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>, Error> {
+pub(crate) fn find_files<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>, Error> {
     let mut coll = Vec::new();
 
     for entry in fs::read_dir(path)? {
@@ -67,7 +220,7 @@ fn find_files<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>, Error> {
 /// Read file directly to memory. The output of an individual benchmark
 /// output is quite small, so reading the full thing will no create any
 /// issues.
-fn read_file<P: AsRef<Path>>(path: P) -> Result<FileContent, Error> {
+pub(crate) fn read_file<P: AsRef<Path>>(path: P) -> Result<FileContent, Error> {
     let mut file = File::open(path.as_ref())?;
     let mut contents = String::new();
     file.read_to_string(&mut contents)?;
@@ -80,6 +233,38 @@ impl Iterator for FileScraper {
     fn next(&mut self) -> Option<Self::Item> {
         let path = self.files.get(self.count)?;
         self.count += 1;
-        Some(read_file(path.as_path()))
+
+        match &self.git {
+            Some((source, commit)) => Some(source.read_file(commit, &path.to_string_lossy())),
+            None => Some(read_file(path.as_path())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_source() {
+        assert_eq!(
+            "git:HEAD~1:benchmarks/results"
+                .parse::<GitSource>()
+                .unwrap(),
+            GitSource::new(".", "HEAD~1", "benchmarks/results")
+        );
+        assert_eq!(
+            "git:v1.0:".parse::<GitSource>().unwrap(),
+            GitSource::new(".", "v1.0", ".")
+        );
+        assert!("git:HEAD".parse::<GitSource>().is_err());
+        assert!("git::results".parse::<GitSource>().is_err());
+        assert!("HEAD:results".parse::<GitSource>().is_err());
+    }
+
+    #[test]
+    fn test_fnv1a() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
     }
 }
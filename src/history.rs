@@ -0,0 +1,603 @@
+//! Local history of benchmark runs, stored as an append-only directory of JSON
+//! snapshots. Every recorded run is written to its own file, which is never
+//! modified afterwards.
+
+use crate::stats;
+use crate::tables::{ChangeKind, ChangeTable, ChangeTableEntry, TrendTable, TrendTableEntry};
+use crate::{ExtrinsicCollection, ExtrinsicId, ExtrinsicResult, Metric, RoundBy};
+use failure::Error;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Directory of the history if none is specified.
+pub const DEFAULT_HISTORY_DIR: &str = ".bench-history";
+
+/// Version of the snapshot format, increased on incompatible changes.
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// Lower bound of the noise of a series, relative to its median. Without it, a
+/// series whose averages repeat exactly has no noise at all, and any deviation
+/// would be infinitely many standard deviations away.
+const MIN_RELATIVE_SIGMA: f64 = 0.001;
+
+#[allow(non_local_definitions)]
+mod error {
+    use crate::ExtrinsicId;
+
+    #[derive(Debug, Fail)]
+    pub(super) enum HistoryError {
+        #[fail(display = "no results to record")]
+        EmptyRun,
+        #[fail(display = "invalid snapshot {}: {}", 0, 1)]
+        InvalidSnapshot(String, String),
+        #[fail(display = "snapshot {} has unsupported version {}", 0, 1)]
+        UnsupportedVersion(String, u32),
+        #[fail(display = "extrinsic {} is not part of any recorded run", 0)]
+        UnknownExtrinsic(ExtrinsicId),
+    }
+}
+
+use error::HistoryError::*;
+
+/// Describes where and when a run was measured.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RunMetadata {
+    /// Name of the run, usually the commit which was benchmarked.
+    pub label: String,
+    /// Time of recording, in seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub commit: Option<String>,
+    pub machine: Option<String>,
+    pub runtime: Option<String>,
+    /// Speed factor of the machine relative to a reference machine, e.g. `1.25`
+    /// if it takes 25% longer, see `normalize`.
+    #[serde(default)]
+    pub speed_factor: Option<f64>,
+}
+
+impl RunMetadata {
+    /// Creates the metadata of a run recorded now.
+    pub fn new(label: &str) -> Self {
+        RunMetadata {
+            label: label.to_string(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0),
+            commit: None,
+            machine: None,
+            runtime: None,
+            speed_factor: None,
+        }
+    }
+}
+
+/// Thresholds of the change detection, see `detect_changes`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectionConfig {
+    /// Standard errors by which a level shift, and standard deviations by which an
+    /// anomaly, must exceed the noise.
+    pub threshold: f64,
+    /// Smallest change which is reported, in percent of the median time.
+    pub min_change: f64,
+}
+
+impl Default for DetectionConfig {
+    fn default() -> Self {
+        DetectionConfig {
+            threshold: 3.0,
+            min_change: 5.0,
+        }
+    }
+}
+
+/// A recorded run, as loaded from the history.
+#[derive(Debug)]
+pub struct Run {
+    pub metadata: RunMetadata,
+    pub collection: ExtrinsicCollection,
+}
+
+#[derive(Serialize)]
+struct SnapshotRef<'a> {
+    version: u32,
+    metadata: &'a RunMetadata,
+    results: &'a [ExtrinsicResult],
+}
+
+#[derive(Deserialize)]
+struct Snapshot {
+    version: u32,
+    metadata: RunMetadata,
+    results: Vec<ExtrinsicResult>,
+}
+
+/// Directory of snapshots, named `<sequence>-<label>.json`. The sequence number
+/// keeps the files in the order the runs were recorded.
+#[derive(Debug)]
+pub struct HistoryStore {
+    dir: PathBuf,
+}
+
+impl HistoryStore {
+    /// Opens the history at the given directory, which is created if missing.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self, Error> {
+        fs::create_dir_all(dir.as_ref())?;
+
+        Ok(HistoryStore {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+    /// Appends the run to the history and returns the path of its snapshot.
+    pub fn record(
+        &self,
+        metadata: &RunMetadata,
+        collection: &ExtrinsicCollection,
+    ) -> Result<PathBuf, Error> {
+        if collection.is_empty() {
+            return Err(EmptyRun.into());
+        }
+
+        let content = serde_json::to_string_pretty(&SnapshotRef {
+            version: SNAPSHOT_VERSION,
+            metadata,
+            results: &collection.results,
+        })?;
+
+        let mut sequence = self.last_sequence()? + 1;
+        loop {
+            let path = self.dir.join(format!(
+                "{:06}-{}.json",
+                sequence,
+                sanitize_label(&metadata.label)
+            ));
+
+            // Never overwrite a snapshot, e.g. when recording concurrently
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    file.write_all(content.as_bytes())?;
+                    return Ok(path);
+                }
+                Err(err) if err.kind() == ErrorKind::AlreadyExists => sequence += 1,
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+    /// Loads all recorded runs, oldest first.
+    pub fn runs(&self) -> Result<Vec<Run>, Error> {
+        self.snapshot_paths()?
+            .into_iter()
+            .map(|(_, path)| load_snapshot(&path))
+            .collect()
+    }
+    /// Snapshot files with their sequence number, ordered by the sequence.
+    fn snapshot_paths(&self) -> Result<Vec<(u64, PathBuf)>, Error> {
+        let mut paths = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+
+            let sequence = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.split('-').next())
+                .and_then(|sequence| sequence.parse::<u64>().ok());
+
+            if let Some(sequence) = sequence {
+                paths.push((sequence, path));
+            }
+        }
+
+        paths.sort();
+        Ok(paths)
+    }
+    fn last_sequence(&self) -> Result<u64, Error> {
+        Ok(self
+            .snapshot_paths()?
+            .last()
+            .map(|(sequence, _)| *sequence)
+            .unwrap_or(0))
+    }
+}
+
+/// Makes the runs comparable which were measured on different machines. With a
+/// reference set, the times of each run are adjusted to the machine of the latest
+/// run, see `ExtrinsicCollection::hardware_factor`. Otherwise, the times of each
+/// run are divided by the speed factor of its metadata, runs without one are left
+/// as they are.
+pub fn normalize(runs: &mut [Run], reference: &[ExtrinsicId], metric: Metric) -> Result<(), Error> {
+    let (latest, runs) = match runs.split_last_mut() {
+        Some(split) => split,
+        None => return Ok(()),
+    };
+
+    if reference.is_empty() {
+        for run in runs.iter_mut().chain(std::iter::once(latest)) {
+            if let Some(factor) = run.metadata.speed_factor {
+                run.collection.scale(factor)?;
+            }
+        }
+        return Ok(());
+    }
+
+    for run in runs {
+        let factor = run
+            .collection
+            .hardware_factor(&latest.collection, reference, metric)?;
+        run.collection.scale(factor)?;
+    }
+
+    Ok(())
+}
+
+/// Statistics of the given extrinsic in each run, in the order the runs were
+/// recorded. Runs which do not contain the extrinsic are skipped.
+pub fn trend<'a>(
+    runs: &'a [Run],
+    id: &ExtrinsicId,
+    metric: Metric,
+) -> Result<TrendTable<'a>, Error> {
+    let mut table = TrendTable::new();
+    let mut first: Option<f64> = None;
+    let mut previous: Option<f64> = None;
+
+    let percentage =
+        |from: Option<f64>, to: f64| from.map(|from| ((to - from) / from * 100.0).round_by(4));
+
+    for run in runs {
+        let times = run
+            .collection
+            .repeat_times(&id.pallet, &id.extrinsic, None, metric);
+        if times.is_empty() {
+            continue;
+        }
+
+        let average = times.iter().sum::<f64>() / times.len() as f64;
+        table.push(TrendTableEntry {
+            metadata: &run.metadata,
+            average: average.round_by(4),
+            // Unwrapping is ok, since `times` is not empty
+            median: stats::median(&times).unwrap().round_by(4),
+            std_dev: stats::standard_deviation(&times).round_by(4),
+            change_previous: percentage(previous, average),
+            change_first: percentage(first, average),
+        });
+
+        first = first.or(Some(average));
+        previous = Some(average);
+    }
+
+    if table.is_empty() {
+        return Err(UnknownExtrinsic(id.clone()).into());
+    }
+
+    Ok(table)
+}
+
+/// Detects level shifts and anomalous runs in the average times of each extrinsic
+/// over the runs.
+///
+/// The noise of a series is estimated from the differences between successive
+/// runs, but is at least the average standard error of the runs and 0.1% of the
+/// median. Series whose median is zero are skipped. Level shifts are
+/// detected with CUSUM-based binary segmentation (see `stats::change_points`),
+/// where each level contains at least three runs. Within a level, runs whose
+/// average deviates from the median of the level by more than the threshold are
+/// reported as anomalies. A deviation of the newest runs is therefore reported
+/// as an anomaly, until enough runs confirm the new level.
+pub fn detect_changes<'a>(
+    runs: &'a [Run],
+    metric: Metric,
+    config: &DetectionConfig,
+) -> Result<ChangeTable<'a>, Error> {
+    let mut ids: Vec<(&str, &str)> = runs
+        .iter()
+        .flat_map(|run| &run.collection.results)
+        .map(|result| (result.pallet.as_str(), result.extrinsic.as_str()))
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+
+    let mut table = ChangeTable::new();
+    for (pallet, extrinsic) in ids {
+        // Average time and standard error of each run which contains the extrinsic
+        let (indices, series): (Vec<usize>, Vec<(f64, f64)>) = runs
+            .iter()
+            .enumerate()
+            .filter_map(|(index, run)| {
+                let times = run.collection.repeat_times(pallet, extrinsic, None, metric);
+                if times.is_empty() {
+                    return None;
+                }
+
+                let average = times.iter().sum::<f64>() / times.len() as f64;
+                let standard_error =
+                    stats::standard_deviation(&times) / (times.len() as f64).sqrt();
+                Some((index, (average, standard_error)))
+            })
+            .unzip();
+
+        let averages: Vec<f64> = series.iter().map(|(average, _)| *average).collect();
+        let standard_error =
+            series.iter().map(|(_, error)| error).sum::<f64>() / series.len() as f64;
+        // Unwrapping is ok, since every extrinsic is part of at least one run
+        let median = stats::median(&averages).unwrap().abs();
+        if median == 0.0 {
+            continue;
+        }
+        let sigma = stats::successive_difference_sigma(&averages)
+            .max(standard_error)
+            .max(MIN_RELATIVE_SIGMA * median);
+        let min_change = config.min_change / 100.0 * median;
+
+        let points = stats::change_points(&averages, sigma, config.threshold, min_change, 3);
+
+        let mut bounds = vec![0];
+        bounds.extend(&points);
+        bounds.push(averages.len());
+        // Unwrapping is ok, since each level contains at least one run
+        let levels: Vec<f64> = bounds
+            .windows(2)
+            .map(|w| stats::median(&averages[w[0]..w[1]]).unwrap())
+            .collect();
+
+        let mut push = |position: usize, kind, before: f64, after: f64| {
+            table.push(ChangeTableEntry {
+                pallet,
+                extrinsic,
+                metadata: &runs[indices[position]].metadata,
+                run_index: indices[position],
+                kind,
+                before: before.round_by(4),
+                after: after.round_by(4),
+                percentage: if before == 0.0 {
+                    None
+                } else {
+                    Some(((after - before) / before * 100.0).round_by(4))
+                },
+                score: ((after - before) / sigma).round_by(4),
+            });
+        };
+
+        for (level, &point) in points.iter().enumerate() {
+            push(point, ChangeKind::Shift, levels[level], levels[level + 1]);
+        }
+
+        for (level, w) in bounds.windows(2).enumerate() {
+            for (position, &average) in averages.iter().enumerate().take(w[1]).skip(w[0]) {
+                let deviation = average - levels[level];
+                if deviation.abs() > config.threshold * sigma && deviation.abs() >= min_change {
+                    push(position, ChangeKind::Anomaly, levels[level], average);
+                }
+            }
+        }
+    }
+
+    table.sort_by_extrinsic();
+    Ok(table)
+}
+
+fn load_snapshot(path: &Path) -> Result<Run, Error> {
+    let content = fs::read_to_string(path)?;
+    let snapshot: Snapshot = serde_json::from_str(&content)
+        .map_err(|err| InvalidSnapshot(path.display().to_string(), err.to_string()))?;
+
+    if snapshot.version != SNAPSHOT_VERSION {
+        return Err(UnsupportedVersion(path.display().to_string(), snapshot.version).into());
+    }
+
+    Ok(Run {
+        metadata: snapshot.metadata,
+        collection: ExtrinsicCollection {
+            results: snapshot.results,
+        },
+    })
+}
+
+/// Replaces the characters of the label which are not safe in a file name.
+fn sanitize_label(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "bench-review-history-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn collection() -> ExtrinsicCollection {
+        let mut result = fixtures::result("balances", "transfer", &[(100, 10), (120, 12)]);
+        result.highest_values = vec![1_000];
+        for step in &mut result.steps_repeats {
+            step.reads = 2;
+            step.writes = 1;
+        }
+
+        let mut collection = ExtrinsicCollection::new();
+        collection.push(result);
+        collection
+    }
+
+    /// Run of `balances.transfer` with one repeat per given time.
+    fn run(label: &str, times: &[u64]) -> Run {
+        let times: Vec<(u64, u64)> = times.iter().map(|&time| (time, 0)).collect();
+        let mut collection = ExtrinsicCollection::new();
+        collection.push(fixtures::result("balances", "transfer", &times));
+
+        Run {
+            metadata: RunMetadata::new(label),
+            collection,
+        }
+    }
+
+    #[test]
+    fn test_detect_changes() {
+        let runs: Vec<Run> = [100, 101, 99, 100, 120, 121, 119, 160, 120]
+            .iter()
+            .enumerate()
+            .map(|(index, &time)| run(&format!("run-{}", index), &[time, time]))
+            .collect();
+
+        let table =
+            detect_changes(&runs, Metric::ExtrinsicTime, &DetectionConfig::default()).unwrap();
+        let rows = table.raw_list();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(
+            (rows[0].2, rows[0].3, rows[0].4, rows[0].5, rows[0].6),
+            ("run-4", ChangeKind::Shift, 100.0, 120.0, Some(20.0))
+        );
+        assert_eq!(
+            (rows[1].2, rows[1].3, rows[1].4, rows[1].5),
+            ("run-7", ChangeKind::Anomaly, 120.0, 160.0)
+        );
+
+        // Changes below the minimal change are not reported
+        let config = DetectionConfig {
+            min_change: 50.0,
+            ..Default::default()
+        };
+        assert!(detect_changes(&runs, Metric::ExtrinsicTime, &config)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_detect_changes_noise() {
+        // The spread of the repeats hides the shift
+        let runs: Vec<Run> = [100, 101, 99, 100, 104, 105, 103, 104]
+            .iter()
+            .map(|&time| run("run", &[time - 40, time + 40, time - 40, time + 40]))
+            .collect();
+
+        let config = DetectionConfig {
+            min_change: 0.0,
+            ..Default::default()
+        };
+        assert!(detect_changes(&runs, Metric::ExtrinsicTime, &config)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_detect_changes_without_noise() {
+        // The averages repeat exactly and the repeats do not vary
+        let runs: Vec<Run> = [100, 100, 100, 100, 106, 100, 100]
+            .iter()
+            .map(|&time| run("run", &[time, time]))
+            .collect();
+
+        let table =
+            detect_changes(&runs, Metric::ExtrinsicTime, &DetectionConfig::default()).unwrap();
+        let rows = table.raw_list();
+        assert_eq!(rows.len(), 1);
+        // The noise is 0.1% of the median
+        assert_eq!(
+            (rows[0].3, rows[0].6, rows[0].7),
+            (ChangeKind::Anomaly, Some(6.0), 60.0)
+        );
+
+        // Nothing can be told about a series whose median is zero
+        let runs: Vec<Run> = [0, 0, 0, 5, 0]
+            .iter()
+            .map(|&time| run("run", &[time, time]))
+            .collect();
+        assert!(
+            detect_changes(&runs, Metric::ExtrinsicTime, &DetectionConfig::default())
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        let times = |runs: &[Run]| -> Vec<f64> {
+            runs.iter()
+                .map(|run| run.collection.results[0].average_time(Metric::ExtrinsicTime))
+                .collect()
+        };
+
+        // Measured on a machine which takes twice as long
+        let mut runs = vec![run("a", &[100]), run("b", &[240]), run("c", &[110])];
+        runs[1].metadata.speed_factor = Some(2.0);
+        normalize(&mut runs, &[], Metric::ExtrinsicTime).unwrap();
+        assert_eq!(times(&runs), vec![100.0, 120.0, 110.0]);
+
+        // The only reference extrinsic explains every change
+        let id: ExtrinsicId = "balances.transfer".parse().unwrap();
+        normalize(&mut runs, &[id], Metric::ExtrinsicTime).unwrap();
+        assert_eq!(times(&runs), vec![110.0, 110.0, 110.0]);
+    }
+
+    #[test]
+    fn test_sanitize_label() {
+        assert_eq!(sanitize_label("a1b2c3"), "a1b2c3");
+        assert_eq!(sanitize_label("v0.9.1"), "v0.9.1");
+        assert_eq!(
+            sanitize_label("feature/fast-transfer"),
+            "feature_fast_transfer"
+        );
+    }
+
+    #[test]
+    fn test_record_and_load() {
+        let dir = temp_dir("record");
+        let store = HistoryStore::open(&dir).unwrap();
+        assert!(store.runs().unwrap().is_empty());
+
+        let mut first = RunMetadata::new("a1b2c3");
+        first.machine = Some("bench-01".to_string());
+        let second = RunMetadata::new("feature/x");
+
+        let path = store.record(&first, &collection()).unwrap();
+        assert_eq!(path, dir.join("000001-a1b2c3.json"));
+        let path = store.record(&second, &collection()).unwrap();
+        assert_eq!(path, dir.join("000002-feature_x.json"));
+
+        let runs = store.runs().unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].metadata, first);
+        assert_eq!(runs[1].metadata, second);
+
+        let result = &runs[0].collection.results[0];
+        assert_eq!(result.pallet, "balances");
+        assert_eq!(result.highest_values, vec![1_000]);
+        assert_eq!(result.steps_repeats[1].extrinsic_time, 120);
+        assert_eq!(result.steps_repeats[1].reads, 2);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_record_empty_run() {
+        let dir = temp_dir("empty");
+        let store = HistoryStore::open(&dir).unwrap();
+        assert!(store
+            .record(&RunMetadata::new("a1b2c3"), &ExtrinsicCollection::new())
+            .is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -4,35 +4,73 @@ use failure::Error;
 #[macro_use]
 extern crate prettytable;
 
+pub mod baseline;
+pub mod capacity;
+pub mod codegen;
 pub mod filescraper;
+pub mod history;
+pub mod limits;
+pub mod notify;
 mod parser;
+pub mod review;
+mod stats;
 pub mod tables;
+pub mod watch;
+pub mod weightfile;
+pub mod weights;
 
-pub use filescraper::{FileContent, FileScraper};
-use tables::{PerExtrTable, PerExtrTableEntry, StepIncr, StepIncrTable, StepIncrTableEntry};
+pub use baseline::{BaselineConfig, BaselineSnapshot};
+pub use capacity::CapacityConfig;
+pub use filescraper::{FileContent, FileScraper, GitSource};
+pub use history::{HistoryStore, RunMetadata};
+pub use limits::LimitsConfig;
+pub use notify::NotifyConfig;
+pub use review::ReviewLedger;
+use tables::{
+    CapacityTable, CapacityTableEntry, ComparisonTable, ComparisonTableEntry, ComponentCheckTable,
+    ComponentCheckTableEntry, ComponentFinding, ComponentStep, ComponentTable, ComponentTableEntry,
+    CostChange, LimitTable, LimitTableEntry, PerExtrTable, PerExtrTableEntry, RankTable,
+    RankTableEntry, Significance, StepChange, StepComparisonTable, StepComparisonTableEntry,
+    StepIncr, StepIncrTable, StepIncrTableEntry, WeightComparisonTable, WeightComparisonTableEntry,
+    WeightStatus, WeightTable, WeightTableEntry,
+};
+use weightfile::WeightFunction;
+pub use weights::WeightConfig;
 
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct ExtrinsicResult {
     pallet: String,
     extrinsic: String,
     steps: usize,
     repeats: usize,
     input_var_names: Vec<String>,
+    /// Highest component values as specified by the header, empty if not specified.
+    highest_values: Vec<u64>,
     steps_repeats: Vec<StepRepeatEntry>,
+    /// FNV-1a hash of the result file, zero if not parsed from a file.
+    #[serde(default)]
+    file_hash: u64,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 struct StepRepeatEntry {
     input_vars: Vec<u64>,
     extrinsic_time: u64,
     storage_root_time: u64,
+    /// Database operations, zero if the result file does not contain them.
+    reads: u64,
+    writes: u64,
 }
 
 /// Convenience trait. Round based on the specified
-/// number of digits.
+/// number of digits. Values which round to zero are always positive, so that
+/// the tables never display `-0`.
 ///
 /// # Example
 /// ```ignore
@@ -46,7 +84,8 @@ trait RoundBy {
 impl RoundBy for f64 {
     fn round_by(&self, by: i32) -> Self {
         let precision = 10.0_f64.powi(by);
-        (self * precision).round() / precision
+        // Adding zero turns `-0.0` into `0.0`
+        (self * precision).round() / precision + 0.0
     }
 }
 
@@ -72,27 +111,280 @@ impl ExtrinsicResult {
         self.steps_repeats
             .iter()
             .map(|e| e.extrinsic_time)
-            .fold(0, |acc, num| acc + num)
+            .sum::<u64>()
             .calc_average(self.steps_repeats.len())
     }
     fn average_storage_root_time(&self) -> f64 {
         self.steps_repeats
             .iter()
             .map(|e| e.storage_root_time)
-            .fold(0, |acc, num| acc + num)
+            .sum::<u64>()
             .calc_average(self.steps_repeats.len())
     }
+    fn average_time(&self, metric: Metric) -> f64 {
+        match metric {
+            Metric::ExtrinsicTime => self.average_extrinsic_time(),
+            Metric::StorageRootTime => self.average_storage_root_time(),
+            Metric::TotalTime => self.average_extrinsic_time() + self.average_storage_root_time(),
+        }
+    }
+    /// The `p`-th percentile of the time of all repeats of all steps.
+    fn percentile_time(&self, metric: Metric, p: f64) -> f64 {
+        let times: Vec<u64> = self.steps_repeats.iter().map(|e| e.time(metric)).collect();
+        stats::percentile(&times, p).unwrap_or(0) as f64
+    }
+    /// Time at the highest component values. If the header specifies the highest
+    /// values, the time is extrapolated with a linear model fitted through all repeats.
+    /// Otherwise, it's the average time of the step with the highest component values.
+    fn worst_case_time(&self, metric: Metric) -> f64 {
+        if !self.highest_values.is_empty()
+            && self.highest_values.len() == self.input_var_names.len()
+        {
+            if let Some(time) = self.extrapolate_time(metric, &self.highest_values) {
+                return time;
+            }
+        }
+
+        self.worst_case_average(|e| e.time(metric))
+    }
+    /// Average amount of database operations of the step with the highest component
+    /// values. Unlike the time, it's not extrapolated.
+    fn worst_case_db_ops(&self) -> f64 {
+        self.worst_case_average(|e| e.reads + e.writes)
+    }
+    /// Average of the given value over the repeats of the step with the highest
+    /// component values.
+    fn worst_case_average<F>(&self, value: F) -> f64
+    where
+        F: Fn(&StepRepeatEntry) -> u64,
+    {
+        let worst_case = match self
+            .steps_repeats
+            .iter()
+            .map(|e| &e.input_vars)
+            .max_by(|a, b| cmp_components(a, b))
+        {
+            Some(input_vars) => input_vars,
+            None => return 0.0,
+        };
+
+        let values: Vec<u64> = self
+            .steps_repeats
+            .iter()
+            .filter(|e| &e.input_vars == worst_case)
+            .map(value)
+            .collect();
+
+        values.iter().sum::<u64>().calc_average(values.len())
+    }
+    /// Evaluates a linear model, fitted through the times of all repeats, at the
+    /// given component values.
+    fn extrapolate_time(&self, metric: Metric, input_vars: &[u64]) -> Option<f64> {
+        let (xs, ys): (Vec<Vec<f64>>, Vec<f64>) = self
+            .steps_repeats
+            .iter()
+            .map(|e| {
+                (
+                    e.input_vars.iter().map(|&var| var as f64).collect(),
+                    e.time(metric) as f64,
+                )
+            })
+            .unzip();
+
+        let coefficients = stats::fit_linear_model(&xs, &ys)?;
+        Some(
+            coefficients[0]
+                + coefficients[1..]
+                    .iter()
+                    .zip(input_vars)
+                    .map(|(coefficient, &var)| coefficient * var as f64)
+                    .sum::<f64>(),
+        )
+    }
+}
+
+impl StepRepeatEntry {
+    fn time(&self, metric: Metric) -> u64 {
+        match metric {
+            Metric::ExtrinsicTime => self.extrinsic_time,
+            Metric::StorageRootTime => self.storage_root_time,
+            Metric::TotalTime => self.extrinsic_time + self.storage_root_time,
+        }
+    }
+}
+
+/// The measurement which is used for selecting the baseline and for sorting.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Metric {
+    #[default]
+    ExtrinsicTime,
+    StorageRootTime,
+    /// Extrinsic time + storage root time.
+    TotalTime,
+}
+
+/// Identifies an extrinsic by its pallet and name, written as `pallet.extrinsic`
+/// (e.g. `balances.transfer`).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExtrinsicId {
+    pub pallet: String,
+    pub extrinsic: String,
+}
+
+impl ExtrinsicId {
+    fn matches(&self, result: &ExtrinsicResult) -> bool {
+        self.pallet == result.pallet && self.extrinsic == result.extrinsic
+    }
+}
+
+impl FromStr for ExtrinsicId {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, '.');
+        match (parts.next(), parts.next()) {
+            (Some(pallet), Some(extrinsic)) if !pallet.is_empty() && !extrinsic.is_empty() => {
+                Ok(ExtrinsicId {
+                    pallet: pallet.to_string(),
+                    extrinsic: extrinsic.to_string(),
+                })
+            }
+            _ => Err(InvalidExtrinsicId(s.to_string()).into()),
+        }
+    }
+}
+
+impl fmt::Display for ExtrinsicId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.pallet, self.extrinsic)
+    }
+}
+
+/// Determines which extrinsic is used as the baseline when calculating the
+/// per-extrinsic ratios and increases.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Baseline {
+    /// The fastest extrinsic, based on the given metric. Since the fastest
+    /// extrinsic can change between runs, ratios are not comparable across runs.
+    Fastest(Metric),
+    /// A fixed reference extrinsic, e.g. `system.remark`.
+    Reference(ExtrinsicId),
+}
+
+impl Default for Baseline {
+    fn default() -> Self {
+        Baseline::Fastest(Metric::default())
+    }
+}
+
+/// Makes the times of two runs comparable which were measured on different
+/// machines. Like the ratios to a reference extrinsic (see `Baseline`), the
+/// adjusted times no longer depend on the speed of the machine.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Normalization {
+    /// Speed factors of the old and the new machine, relative to a reference
+    /// machine, e.g. `1.25` for a machine which takes 25% longer. The times of
+    /// each run are divided by the factor of its machine.
+    SpeedFactors(f64, f64),
+    /// Extrinsics whose code did not change between the runs, e.g.
+    /// `system.remark`. The change of their times is attributed to the hardware,
+    /// the times of the new run are divided by it.
+    ReferenceSet(Vec<ExtrinsicId>),
+}
+
+impl Normalization {
+    /// Adjusts the times of both runs. Returns the hardware factor, i.e. how much
+    /// longer the new machine takes than the old one.
+    pub fn apply(
+        &self,
+        old: &mut ExtrinsicCollection,
+        new: &mut ExtrinsicCollection,
+        metric: Metric,
+    ) -> Result<f64, Error> {
+        let (old_factor, new_factor) = match self {
+            Normalization::SpeedFactors(old_factor, new_factor) => (*old_factor, *new_factor),
+            Normalization::ReferenceSet(ids) => (1.0, new.hardware_factor(old, ids, metric)?),
+        };
+
+        old.scale(old_factor)?;
+        new.scale(new_factor)?;
+
+        Ok((new_factor / old_factor).round_by(4))
+    }
+}
+
+/// Determines which step of an extrinsic is used as the baseline when
+/// calculating the per-step ratios and increases.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepBaseline {
+    /// The step with the lowest average time of the given metric. With noisy
+    /// measurements, this is not necessarily the step with the smallest input.
+    Fastest(Metric),
+    /// The step with the lowest component values (smallest sum, ties are
+    /// resolved by comparing the values in order).
+    LowestComponents,
+    /// The step with the lowest value of the given component (e.g. `u`), ties
+    /// are resolved like `LowestComponents`. Falls back to `LowestComponents`
+    /// for extrinsics which do not have that component.
+    Component(String),
+}
+
+impl Default for StepBaseline {
+    fn default() -> Self {
+        StepBaseline::Fastest(Metric::default())
+    }
+}
+
+/// Orders the input variables of two steps by the sum of their values,
+/// followed by the values themselves.
+fn cmp_components(a: &[u64], b: &[u64]) -> Ordering {
+    a.iter()
+        .sum::<u64>()
+        .cmp(&b.iter().sum::<u64>())
+        .then_with(|| a.cmp(b))
 }
 
-#[derive(Debug, Fail)]
-enum ExtrinsicCollectionError {
-    #[fail(display = "collection does not contain any results")]
-    EmptyResults,
+// The `Fail` derive generates its impls inside of an anonymous constant, so the
+// lint can only be allowed on an enclosing module.
+#[allow(non_local_definitions)]
+mod error {
+    use crate::ExtrinsicId;
+
+    #[derive(Debug, Fail)]
+    pub(super) enum ExtrinsicCollectionError {
+        #[fail(display = "collection does not contain any results")]
+        EmptyResults,
+        #[fail(display = "invalid extrinsic \"{}\", expected <pallet>.<extrinsic>", 0)]
+        InvalidExtrinsicId(String),
+        #[fail(display = "reference extrinsic {} is not part of the results", 0)]
+        MissingReference(ExtrinsicId),
+        #[fail(display = "overhead extrinsic {} is not part of the results", 0)]
+        MissingOverhead(ExtrinsicId),
+        #[fail(display = "invalid speed factor {}, expected a positive number", 0)]
+        InvalidSpeedFactor(f64),
+        #[fail(display = "reference set does not contain any extrinsics")]
+        EmptyReferenceSet,
+        #[fail(
+            display = "can not fit a model for {}.{}, the components do not vary independently",
+            pallet, extrinsic
+        )]
+        UnfittableModel { pallet: String, extrinsic: String },
+        #[fail(
+            display = "average time of {}.{} is zero after subtracting the overhead of {}",
+            pallet, extrinsic, overhead
+        )]
+        ZeroNetTime {
+            pallet: String,
+            extrinsic: String,
+            overhead: ExtrinsicId,
+        },
+    }
 }
 
-use ExtrinsicCollectionError::*;
+use error::ExtrinsicCollectionError::*;
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ExtrinsicCollection {
     results: Vec<ExtrinsicResult>,
 }
@@ -106,149 +398,1030 @@ impl ExtrinsicCollection {
     pub fn push(&mut self, result: ExtrinsicResult) {
         self.results.push(result);
     }
-    pub fn generate_ratio_table(&self) -> Result<PerExtrTable, Error> {
+    /// Amount of extrinsics in the collection.
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+    /// Treats the given extrinsic (e.g. `system.remark`) as fixed dispatch overhead:
+    /// the average times of its step with the lowest component values (e.g. zero
+    /// input) are subtracted from every repeat of all other extrinsics, so that the
+    /// reviews display net times. The overhead extrinsic itself gets removed from
+    /// the collection. Times of single repeats can not go below zero, but the
+    /// average extrinsic and storage root time of each extrinsic must stay above
+    /// zero, since the reviews divide by them.
+    pub fn subtract_overhead(&mut self, overhead: &ExtrinsicId) -> Result<(), Error> {
+        let (overhead_results, results): (Vec<ExtrinsicResult>, Vec<ExtrinsicResult>) = self
+            .results
+            .drain(..)
+            .partition(|result| overhead.matches(result));
+
+        self.results = results;
+
+        // Select the repeats of the step with the lowest component values.
+        let lowest = overhead_results
+            .iter()
+            .flat_map(|result| &result.steps_repeats)
+            .map(|step| &step.input_vars)
+            .min_by(|a, b| cmp_components(a, b))
+            .ok_or_else(|| MissingOverhead(overhead.clone()))?;
+
+        let repeats: Vec<&StepRepeatEntry> = overhead_results
+            .iter()
+            .flat_map(|result| &result.steps_repeats)
+            .filter(|step| &step.input_vars == lowest)
+            .collect();
+
+        let extrinsic_overhead = repeats
+            .iter()
+            .map(|step| step.extrinsic_time)
+            .sum::<u64>()
+            .calc_average(repeats.len())
+            .round() as u64;
+        let storage_root_overhead = repeats
+            .iter()
+            .map(|step| step.storage_root_time)
+            .sum::<u64>()
+            .calc_average(repeats.len())
+            .round() as u64;
+
+        for step in self
+            .results
+            .iter_mut()
+            .flat_map(|result| &mut result.steps_repeats)
+        {
+            step.extrinsic_time = step.extrinsic_time.saturating_sub(extrinsic_overhead);
+            step.storage_root_time = step.storage_root_time.saturating_sub(storage_root_overhead);
+        }
+
+        if let Some(result) = self.results.iter().find(|result| {
+            result.average_time(Metric::ExtrinsicTime) <= 0.0
+                || result.average_time(Metric::StorageRootTime) <= 0.0
+        }) {
+            return Err(ZeroNetTime {
+                pallet: result.pallet.clone(),
+                extrinsic: result.extrinsic.clone(),
+                overhead: overhead.clone(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+    /// Divides every measured time by the speed factor of the machine, so that
+    /// the times correspond to the ones of the reference machine.
+    pub fn scale(&mut self, factor: f64) -> Result<(), Error> {
+        if !(factor.is_finite() && factor > 0.0) {
+            return Err(InvalidSpeedFactor(factor).into());
+        }
+
+        for step in self
+            .results
+            .iter_mut()
+            .flat_map(|result| &mut result.steps_repeats)
+        {
+            step.extrinsic_time = (step.extrinsic_time as f64 / factor).round() as u64;
+            step.storage_root_time = (step.storage_root_time as f64 / factor).round() as u64;
+        }
+
+        Ok(())
+    }
+    /// How much longer the machine of this run takes than the one of `reference`:
+    /// the geometric mean of the ratios of the average times of the given
+    /// extrinsics, which must be part of both runs.
+    pub fn hardware_factor(
+        &self,
+        reference: &ExtrinsicCollection,
+        ids: &[ExtrinsicId],
+        metric: Metric,
+    ) -> Result<f64, Error> {
+        if ids.is_empty() {
+            return Err(EmptyReferenceSet.into());
+        }
+
+        let average = |collection: &ExtrinsicCollection, id: &ExtrinsicId| {
+            collection
+                .results
+                .iter()
+                .find(|result| id.matches(result))
+                .map(|result| result.average_time(metric))
+                .filter(|average| *average > 0.0)
+                .ok_or_else(|| MissingReference(id.clone()))
+        };
+
+        let mut log_sum = 0.0;
+        for id in ids {
+            log_sum += (average(self, id)? / average(reference, id)?).ln();
+        }
+
+        Ok((log_sum / ids.len() as f64).exp())
+    }
+    /// Groups the measurements by extrinsic and by step (input vars), summing up the
+    /// measured times of all repeats. Results of the same extrinsic are merged.
+    fn aggregate_steps(&self) -> HashMap<(&str, &str), StepDb<'_>> {
+        // Signature: (pallet, extrinsic) -> (input var names, (input vars) -> totals)
+        let mut db: HashMap<(&str, &str), StepDb> = HashMap::new();
+
+        // For each extrinsic result...
+        for result in &self.results {
+            let (_, sub_map) = db
+                .entry((&result.pallet, &result.extrinsic))
+                .or_insert_with(|| (&result.input_var_names, HashMap::new()));
+
+            // ... and for each of its steps/repeats...
+            for step in &result.steps_repeats {
+                // ... add the measured times of each repeat to the current value,
+                // identified by the step (input vars). Additionally, track the count
+                // of measurements that were added, in order to calculate the average
+                // later on.
+                sub_map
+                    .entry(&step.input_vars)
+                    .or_insert_with(StepTotals::default)
+                    .add(step);
+            }
+        }
+
+        db
+    }
+    /// Calculates the average times of each extrinsic and their ratios compared
+    /// to the baseline extrinsic.
+    pub fn generate_ratio_table(&self, baseline: &Baseline) -> Result<PerExtrTable<'_>, Error> {
         if self.results.is_empty() {
             return Err(EmptyResults.into());
         }
 
-        // find base (lowest value)
-        let base = self
-            .results
-            .iter()
-            .min_by(|a, b| {
-                a.average_extrinsic_time()
-                    .partial_cmp(&b.average_extrinsic_time())
-                    // can occur if there's only one entry
-                    .unwrap_or(Ordering::Equal)
-            })
-            .ok_or(EmptyResults)?
-            .average_extrinsic_time();
+        let base = match baseline {
+            // find base (lowest value)
+            Baseline::Fastest(metric) => self
+                .results
+                .iter()
+                .min_by(|a, b| {
+                    a.average_time(*metric)
+                        .partial_cmp(&b.average_time(*metric))
+                        // can occur if there's only one entry
+                        .unwrap_or(Ordering::Equal)
+                })
+                .ok_or(EmptyResults)?,
+            Baseline::Reference(id) => self
+                .results
+                .iter()
+                .find(|result| id.matches(result))
+                .ok_or_else(|| MissingReference(id.clone()))?,
+        };
+
+        let extrinsic_base = base.average_extrinsic_time();
+        let storage_root_base = base.average_storage_root_time();
+        let total_base = base.average_time(Metric::TotalTime);
 
         let mut table = PerExtrTable::new();
 
         self.results.iter().for_each(|result| {
             let avg_time = result.average_extrinsic_time();
+            let avg_storage_root_time = result.average_storage_root_time();
+            let avg_total_time = result.average_time(Metric::TotalTime);
             table.push(PerExtrTableEntry {
                 pallet: &result.pallet,
                 extrinsic: &result.extrinsic,
                 avg_extrinsic_time: avg_time.round_by(4),
-                avg_storage_root_time: result.average_storage_root_time().round_by(4),
-                ratio: (avg_time / base).round_by(4),
-                percentage: ((avg_time / base - 1.0) * 100.0).round_by(4),
+                avg_storage_root_time: avg_storage_root_time.round_by(4),
+                avg_total_time: avg_total_time.round_by(4),
+                ratio: (avg_time / extrinsic_base).round_by(4),
+                percentage: ((avg_time / extrinsic_base - 1.0) * 100.0).round_by(4),
+                storage_root_ratio: (avg_storage_root_time / storage_root_base).round_by(4),
+                storage_root_percentage: ((avg_storage_root_time / storage_root_base - 1.0)
+                    * 100.0)
+                    .round_by(4),
+                total_ratio: (avg_total_time / total_base).round_by(4),
+                worst_case_time: result.worst_case_time(Metric::ExtrinsicTime).round_by(4),
+                review: None,
             });
         });
 
         Ok(table)
     }
-    pub fn generate_step_table(&self) -> Result<StepIncrTable, Error> {
+    /// Projects how many calls of each extrinsic fit into a block, based on the
+    /// average time (as in `generate_ratio_table`), the 99th percentile of all
+    /// repeats and the step with the highest component values (worst case).
+    pub fn generate_capacity_table(
+        &self,
+        config: &CapacityConfig,
+        metric: Metric,
+    ) -> Result<CapacityTable<'_>, Error> {
         if self.results.is_empty() {
             return Err(EmptyResults.into());
         }
 
-        // Signature: (pallet, extrinsic) -> ((input vars) -> (count, extrinsic time, storage root time))
-        let mut db: HashMap<(&str, &str), HashMap<&Vec<u64>, (usize, u64, u64)>> = HashMap::new();
+        let mut table = CapacityTable::new();
+
+        self.results.iter().for_each(|result| {
+            let avg_time = result.average_time(metric);
+            let p99_time = result.percentile_time(metric, 99.0);
+            let worst_case_time = result.worst_case_time(metric);
+            table.push(CapacityTableEntry {
+                pallet: &result.pallet,
+                extrinsic: &result.extrinsic,
+                avg_time: avg_time.round_by(4),
+                avg_calls: config.calls(avg_time),
+                p99_time,
+                p99_calls: config.calls(p99_time),
+                worst_case_time: worst_case_time.round_by(4),
+                worst_case_calls: config.calls(worst_case_time),
+            });
+        });
+
+        Ok(table)
+    }
+    /// Checks the worst case weight of each extrinsic (see `worst_case_time` and
+    /// `worst_case_db_ops`) against its ceiling. Extrinsics without a ceiling are skipped.
+    pub fn check_limits(&self, config: &LimitsConfig) -> Result<LimitTable<'_>, Error> {
+        if self.results.is_empty() {
+            return Err(EmptyResults.into());
+        }
+
+        let weight_config = config.weight_config();
+        let block_weight = config.block_weight();
+
+        let mut table = LimitTable::new();
 
-        // For each extrinsic result...
         for result in &self.results {
-            // ... and for each of its steps/repeats...
-            for step in &result.steps_repeats {
-                // ... create an entry...
-                db.entry((&result.pallet, &result.extrinsic))
-                    .and_modify(|sub_map| {
-                        // ... and add the measured times of each repeat to the current value,
-                        // identified by the step (input vars). Additionally, track the count
-                        // of measurements that were added, in order to calculate the average
-                        // later on.
-                        sub_map
-                            .entry(&step.input_vars)
-                            .and_modify(|(count, extrinsic_time, storage_root_time)| {
-                                *count += 1;
-                                *extrinsic_time += step.extrinsic_time;
-                                *storage_root_time += step.storage_root_time;
-                            })
-                            .or_insert((1, step.extrinsic_time, step.storage_root_time));
+            let ceiling = match config.ceiling(&result.pallet, &result.extrinsic) {
+                Some(ceiling) => ceiling,
+                None => continue,
+            };
+
+            let weight = weight_config.weight(
+                result.worst_case_time(Metric::ExtrinsicTime),
+                result.worst_case_db_ops(),
+            );
+            let share = weight / block_weight * 100.0;
+
+            table.push(LimitTableEntry {
+                pallet: &result.pallet,
+                extrinsic: &result.extrinsic,
+                worst_case_weight: weights::to_weight(weight),
+                share,
+                ceiling,
+            });
+        }
+
+        Ok(table)
+    }
+    /// Times of all repeats of the given extrinsic, optionally limited to a step.
+    fn repeat_times(
+        &self,
+        pallet: &str,
+        extrinsic: &str,
+        input_vars: Option<&[u64]>,
+        metric: Metric,
+    ) -> Vec<f64> {
+        self.results
+            .iter()
+            .filter(|result| result.pallet == pallet && result.extrinsic == extrinsic)
+            .flat_map(|result| &result.steps_repeats)
+            .filter(|step| input_vars.is_none_or(|vars| step.input_vars == vars))
+            .map(|step| step.time(metric) as f64)
+            .collect()
+    }
+    /// Times of all repeats of the given extrinsic, without the differences between
+    /// its steps: each time is shifted by the distance between the average of its
+    /// step and the average of all repeats. The spread of the times then reflects
+    /// the noise of the repeats rather than the sweep over the components.
+    fn residual_times(&self, pallet: &str, extrinsic: &str, metric: Metric) -> Vec<f64> {
+        let mut steps: Vec<(&[u64], Vec<f64>)> = vec![];
+        for step in self
+            .results
+            .iter()
+            .filter(|result| result.pallet == pallet && result.extrinsic == extrinsic)
+            .flat_map(|result| &result.steps_repeats)
+        {
+            let time = step.time(metric) as f64;
+            match steps
+                .iter_mut()
+                .find(|(input_vars, _)| *input_vars == step.input_vars.as_slice())
+            {
+                Some((_, times)) => times.push(time),
+                None => steps.push((&step.input_vars, vec![time])),
+            }
+        }
+
+        let average = |times: &[f64]| times.iter().sum::<f64>() / times.len() as f64;
+        let all: Vec<f64> = steps.iter().flat_map(|(_, times)| times.clone()).collect();
+        let total_average = average(&all);
+
+        steps
+            .iter()
+            .flat_map(|(_, times)| {
+                let step_average = average(times);
+                times
+                    .iter()
+                    .map(move |time| time - step_average + total_average)
+            })
+            .collect()
+    }
+    /// Average time of all repeats of the given extrinsic, merging duplicate results
+    /// like `aggregate_steps`. `None` if the extrinsic is not part of the collection.
+    fn merged_average_time(&self, pallet: &str, extrinsic: &str, metric: Metric) -> Option<f64> {
+        let times = self.repeat_times(pallet, extrinsic, None, metric);
+        if times.is_empty() {
+            return None;
+        }
+
+        Some(times.iter().sum::<f64>() / times.len() as f64)
+    }
+    /// Compares the average times of this (old) collection with the ones of a new
+    /// collection, matching extrinsics by pallet and name. Extrinsics which only
+    /// exist in one of the collections are listed as removed or added. Duplicate
+    /// results of an extrinsic are merged.
+    pub fn compare<'a>(
+        &'a self,
+        new: &'a ExtrinsicCollection,
+        metric: Metric,
+    ) -> Result<ComparisonTable<'a>, Error> {
+        if self.results.is_empty() && new.results.is_empty() {
+            return Err(EmptyResults.into());
+        }
+
+        let mut table = ComparisonTable::new();
+
+        for result in self.results.iter().chain(&new.results) {
+            let (pallet, extrinsic) = (result.pallet.as_str(), result.extrinsic.as_str());
+            if table.contains(pallet, extrinsic) {
+                continue;
+            }
+
+            let old_time = self.merged_average_time(pallet, extrinsic, metric);
+            let new_time = new.merged_average_time(pallet, extrinsic, metric);
+
+            let (change, percentage) = match (old_time, new_time) {
+                (Some(old_time), Some(new_time)) => (
+                    Some((new_time - old_time).round_by(4)),
+                    Some(((new_time / old_time - 1.0) * 100.0).round_by(4)),
+                ),
+                _ => (None, None),
+            };
+
+            table.push(ComparisonTableEntry {
+                pallet,
+                extrinsic,
+                old_time: old_time.map(|time| time.round_by(4)),
+                new_time: new_time.map(|time| time.round_by(4)),
+                change,
+                percentage,
+                significance: significance(
+                    &self.residual_times(pallet, extrinsic, metric),
+                    &new.residual_times(pallet, extrinsic, metric),
+                ),
+            });
+        }
+
+        Ok(table)
+    }
+    /// Compares the ordering of the extrinsics of both runs, as in the per-extrinsic
+    /// table sorted by the given metric: each extrinsic which exists in both runs is
+    /// ranked by its average time, starting at 1 for the fastest one. The Kendall
+    /// tau and the Spearman rank correlation tell how stable the ordering is, even
+    /// if the absolute times shifted evenly.
+    pub fn compare_ranks<'a>(
+        &'a self,
+        new: &'a ExtrinsicCollection,
+        metric: Metric,
+    ) -> Result<RankTable<'a>, Error> {
+        if self.results.is_empty() || new.results.is_empty() {
+            return Err(EmptyResults.into());
+        }
+
+        // Extrinsics of both runs, with their old and new average time. Duplicate
+        // results of an extrinsic are merged, as in `compare`.
+        let mut pairs: Vec<(&ExtrinsicResult, f64, f64)> = vec![];
+        for result in &self.results {
+            let (pallet, extrinsic) = (result.pallet.as_str(), result.extrinsic.as_str());
+            if pairs
+                .iter()
+                .any(|(r, ..)| r.pallet == pallet && r.extrinsic == extrinsic)
+            {
+                continue;
+            }
+            if let (Some(old_time), Some(new_time)) = (
+                self.merged_average_time(pallet, extrinsic, metric),
+                new.merged_average_time(pallet, extrinsic, metric),
+            ) {
+                pairs.push((result, old_time, new_time));
+            }
+        }
+
+        // Ratios relative to the fastest ranked extrinsic, so that extrinsics which
+        // only exist in one run don't shift them
+        let fastest = |times: &[f64]| times.iter().cloned().fold(f64::INFINITY, f64::min);
+        let old_times: Vec<f64> = pairs.iter().map(|(_, old_time, _)| *old_time).collect();
+        let new_times: Vec<f64> = pairs.iter().map(|(.., new_time)| *new_time).collect();
+        let (old_fastest, new_fastest) = (fastest(&old_times), fastest(&new_times));
+
+        let mut table = RankTable::new();
+        table.kendall_tau = stats::kendall_tau(&old_times, &new_times).map(|tau| tau.round_by(4));
+        table.spearman = stats::spearman(&old_times, &new_times).map(|rho| rho.round_by(4));
+
+        let ranks = stats::ranks(&old_times)
+            .into_iter()
+            .zip(stats::ranks(&new_times));
+        for ((result, old_time, new_time), (old_rank, new_rank)) in pairs.into_iter().zip(ranks) {
+            table.push(RankTableEntry {
+                pallet: &result.pallet,
+                extrinsic: &result.extrinsic,
+                old_rank,
+                new_rank,
+                old_ratio: (old_time / old_fastest).round_by(4),
+                new_ratio: (new_time / new_fastest).round_by(4),
+            });
+        }
+
+        Ok(table)
+    }
+    /// Compares the steps of the extrinsics which exist in both collections, matching
+    /// steps by their input variables. Additionally, a linear model is fitted through
+    /// the average times of the steps of each run, revealing whether the base cost or
+    /// the marginal cost of a component (slope) changed. The marginal costs are only
+    /// compared if both runs have the same components.
+    pub fn compare_steps<'a>(
+        &'a self,
+        new: &'a ExtrinsicCollection,
+        metric: Metric,
+    ) -> Result<StepComparisonTable<'a>, Error> {
+        if self.results.is_empty() && new.results.is_empty() {
+            return Err(EmptyResults.into());
+        }
+
+        let old_db = self.aggregate_steps();
+        let new_db = new.aggregate_steps();
+
+        let mut table = StepComparisonTable::new();
+
+        for (key, (var_names, old_data)) in &old_db {
+            let (new_var_names, new_data) = match new_db.get(key) {
+                Some(entry) => entry,
+                None => continue,
+            };
+
+            let mut input_vars: Vec<&Vec<u64>> = old_data.keys().cloned().collect();
+            input_vars.extend(new_data.keys().filter(|vars| !old_data.contains_key(*vars)));
+            input_vars.sort_by(|a, b| cmp_components(a, b));
+
+            let steps = input_vars
+                .into_iter()
+                .map(|vars| {
+                    let old_time = old_data.get(vars).map(|t| t.average_time(metric));
+                    let new_time = new_data.get(vars).map(|t| t.average_time(metric));
+                    StepChange {
+                        input_vars: vars,
+                        old_time: old_time.map(|time| time.round_by(4)),
+                        new_time: new_time.map(|time| time.round_by(4)),
+                        percentage: match (old_time, new_time) {
+                            (Some(old_time), Some(new_time)) => {
+                                Some(((new_time / old_time - 1.0) * 100.0).round_by(4))
+                            }
+                            _ => None,
+                        },
+                        significance: significance(
+                            &self.repeat_times(key.0, key.1, Some(vars), metric),
+                            &new.repeat_times(key.0, key.1, Some(vars), metric),
+                        ),
+                    }
+                })
+                .collect();
+
+            let fit = |data: &HashMap<&Vec<u64>, StepTotals>| {
+                let (xs, ys): (Vec<Vec<f64>>, Vec<f64>) = data
+                    .iter()
+                    .map(|(vars, totals)| {
+                        (
+                            vars.iter().map(|&var| var as f64).collect(),
+                            totals.average_time(metric),
+                        )
                     })
-                    .or_insert_with(|| {
-                        let mut hm = HashMap::new();
-                        hm.insert(
-                            &step.input_vars,
-                            (1, step.extrinsic_time, step.storage_root_time),
-                        );
-                        hm
-                    });
+                    .unzip();
+                stats::fit_linear_model(&xs, &ys)
+            };
+
+            let mut costs = Vec::new();
+            if var_names == new_var_names {
+                if let (Some(old_model), Some(new_model)) = (fit(old_data), fit(new_data)) {
+                    let names = std::iter::once("base").chain(var_names.iter().map(String::as_str));
+                    for ((name, old_cost), new_cost) in names.zip(old_model).zip(new_model) {
+                        costs.push(CostChange {
+                            name,
+                            old_cost: old_cost.round_by(4),
+                            new_cost: new_cost.round_by(4),
+                            percentage: if old_cost == 0.0 {
+                                None
+                            } else {
+                                Some(((new_cost / old_cost - 1.0) * 100.0).round_by(4))
+                            },
+                        });
+                    }
+                }
             }
+
+            table.push(StepComparisonTableEntry {
+                pallet: key.0,
+                extrinsic: key.1,
+                steps,
+                costs,
+            });
+        }
+
+        Ok(table)
+    }
+    pub fn generate_step_table(&self, baseline: &StepBaseline) -> Result<StepIncrTable<'_>, Error> {
+        if self.results.is_empty() {
+            return Err(EmptyResults.into());
         }
 
+        let db = self.aggregate_steps();
+
         let mut table = StepIncrTable::new();
         // For each extrinsic ...
-        for ((pallet, extrinsic), data) in db {
-            let mut new_entry = StepIncrTableEntry::default();
-            new_entry.pallet = pallet;
-            new_entry.extrinsic = extrinsic;
-
+        for ((pallet, extrinsic), (var_names, data)) in db {
             // ... and for each of its steps...
-            for (input_vars, (count, extrinsic_time, storage_root_time)) in data {
-                // ... calculate the average. Some fields are filled with zeroes and
-                // get adjusted later on, since all averages have to be calculated
-                // first.
-                new_entry.step_incrs.push(StepIncr {
-                    input_vars: input_vars,
-                    avg_extrinsic_time: extrinsic_time.calc_average(count).round_by(4),
-                    avg_storage_root_time: storage_root_time.calc_average(count).round_by(4),
-                    ratio: 0.0,
-                    extrinsic_incr_percentage: 0.0,
-                    storage_root_incr_percentage: 0.0,
+            let mut step_incrs: Vec<StepIncr> = data
+                .into_iter()
+                .map(|(input_vars, totals)| {
+                    // ... calculate the average. Some fields are filled with zeroes and
+                    // get adjusted later on, since all averages have to be calculated
+                    // first.
+                    let avg_extrinsic_time = totals.extrinsic_time.calc_average(totals.count);
+                    let avg_storage_root_time = totals.storage_root_time.calc_average(totals.count);
+                    StepIncr {
+                        input_vars,
+                        avg_extrinsic_time: avg_extrinsic_time.round_by(4),
+                        avg_storage_root_time: avg_storage_root_time.round_by(4),
+                        avg_total_time: (avg_extrinsic_time + avg_storage_root_time).round_by(4),
+                        ratio: 0.0,
+                        extrinsic_incr_percentage: 0.0,
+                        storage_root_ratio: 0.0,
+                        storage_root_incr_percentage: 0.0,
+                        total_ratio: 0.0,
+                    }
                 })
-            }
+                .collect();
 
-            // Get the smallest value of extrinsic time measurement.
-            let extrinsic_base = new_entry
-                .step_incrs
-                .iter()
-                .min_by(|a, b| {
-                    a.avg_extrinsic_time
-                        .partial_cmp(&b.avg_extrinsic_time)
-                        // can occur if there's only one entry
-                        .unwrap_or(Ordering::Equal)
-                })
-                .ok_or(EmptyResults)?
-                .avg_extrinsic_time;
+            // Select the baseline step, depending on the chosen mode.
+            let component = match baseline {
+                StepBaseline::Component(name) => var_names.iter().position(|n| n == name),
+                _ => None,
+            };
 
-            // Get the smallest value of storage root measurement.
-            let storage_root_base = new_entry
-                .step_incrs
-                .iter()
-                .min_by(|a, b| {
-                    a.avg_storage_root_time
-                        .partial_cmp(&b.avg_storage_root_time)
+            let base = match (baseline, component) {
+                (StepBaseline::Fastest(metric), _) => step_incrs.iter().min_by(|a, b| {
+                    a.avg_time(*metric)
+                        .partial_cmp(&b.avg_time(*metric))
                         // can occur if there's only one entry
                         .unwrap_or(Ordering::Equal)
-                })
-                .ok_or(EmptyResults)?
-                .avg_storage_root_time;
+                }),
+                (_, Some(index)) => step_incrs.iter().min_by(|a, b| {
+                    a.input_vars[index]
+                        .cmp(&b.input_vars[index])
+                        .then_with(|| cmp_components(a.input_vars, b.input_vars))
+                }),
+                (_, None) => step_incrs
+                    .iter()
+                    .min_by(|a, b| cmp_components(a.input_vars, b.input_vars)),
+            }
+            .ok_or(EmptyResults)?;
+
+            let base_vars = base.input_vars;
+            let extrinsic_base = base.avg_extrinsic_time;
+            let storage_root_base = base.avg_storage_root_time;
+            let total_base = base.avg_total_time;
 
-            // Based on the smallest value, calculate the increase of each step in percentages.
-            for entry in &mut new_entry.step_incrs {
+            // Based on the baseline step, calculate the increase of each step in percentages.
+            for entry in &mut step_incrs {
                 entry.ratio = (entry.avg_extrinsic_time / extrinsic_base).round_by(4);
                 entry.extrinsic_incr_percentage =
                     ((entry.avg_extrinsic_time / extrinsic_base - 1.0) * 100.0).round_by(4);
+                entry.storage_root_ratio =
+                    (entry.avg_storage_root_time / storage_root_base).round_by(4);
                 entry.storage_root_incr_percentage =
                     ((entry.avg_storage_root_time / storage_root_base - 1.0) * 100.0).round_by(4);
+                entry.total_ratio = (entry.avg_total_time / total_base).round_by(4);
             }
 
-            //percentage: ((avg_time / base - 1.0) * 100.0).round_by(4),
+            table.push(StepIncrTableEntry {
+                pallet,
+                extrinsic,
+                baseline: base_vars,
+                step_incrs,
+            });
+        }
+
+        Ok(table)
+    }
+    /// Groups the steps of each extrinsic by the component which varies. Substrate
+    /// benchmarks vary one component at a time, while the other components sit at
+    /// their maximum value. For each of those groups, the marginal cost per unit of
+    /// the component is calculated (least squares). Steps which do not fit into any
+    /// group are skipped.
+    pub fn generate_component_table(&self) -> Result<ComponentTable<'_>, Error> {
+        if self.results.is_empty() {
+            return Err(EmptyResults.into());
+        }
+
+        let db = self.aggregate_steps();
+
+        let mut table = ComponentTable::new();
+        for ((pallet, extrinsic), (var_names, data)) in db {
+            // Signature: (input vars, avg. extrinsic time, avg. storage root time)
+            let steps: Vec<(&Vec<u64>, f64, f64)> = data
+                .into_iter()
+                .map(|(input_vars, totals)| {
+                    (
+                        input_vars,
+                        totals.extrinsic_time.calc_average(totals.count),
+                        totals.storage_root_time.calc_average(totals.count),
+                    )
+                })
+                .collect();
+
+            // Maximum value of each component.
+            let max_vars: Vec<u64> = (0..var_names.len())
+                .map(|index| steps.iter().map(|s| s.0[index]).max().unwrap_or(0))
+                .collect();
+
+            for (index, component) in var_names.iter().enumerate() {
+                // Select the steps where all other components are at their maximum.
+                let mut group: Vec<&(&Vec<u64>, f64, f64)> = steps
+                    .iter()
+                    .filter(|(input_vars, _, _)| {
+                        input_vars
+                            .iter()
+                            .zip(&max_vars)
+                            .enumerate()
+                            .all(|(other, (value, max))| other == index || value == max)
+                    })
+                    .collect();
+
+                group.sort_by_key(|(input_vars, _, _)| input_vars[index]);
+
+                let extrinsic_points: Vec<(f64, f64)> = group
+                    .iter()
+                    .map(|(input_vars, time, _)| (input_vars[index] as f64, *time))
+                    .collect();
+                let storage_root_points: Vec<(f64, f64)> = group
+                    .iter()
+                    .map(|(input_vars, _, time)| (input_vars[index] as f64, *time))
+                    .collect();
+
+                // The component must vary at least once.
+                let (extrinsic_slope, storage_root_slope) = match (
+                    stats::linear_regression(&extrinsic_points),
+                    stats::linear_regression(&storage_root_points),
+                ) {
+                    (Some((_, ext)), Some((_, root))) => (ext, root),
+                    _ => continue,
+                };
+
+                table.push(ComponentTableEntry {
+                    pallet,
+                    extrinsic,
+                    component,
+                    extrinsic_slope: extrinsic_slope.round_by(4),
+                    storage_root_slope: storage_root_slope.round_by(4),
+                    steps: group
+                        .iter()
+                        .map(
+                            |(input_vars, extrinsic_time, storage_root_time)| ComponentStep {
+                                input_vars,
+                                value: input_vars[index],
+                                avg_extrinsic_time: extrinsic_time.round_by(4),
+                                avg_storage_root_time: storage_root_time.round_by(4),
+                            },
+                        )
+                        .collect(),
+                });
+            }
+        }
+
+        Ok(table)
+    }
+    /// Fits a linear model (least squares) through the weights of all steps of each
+    /// extrinsic, where the weight of a step is based on its average extrinsic time
+    /// and database operations.
+    fn weight_models(&self, config: &WeightConfig) -> Result<Vec<WeightModel<'_>>, Error> {
+        if self.results.is_empty() {
+            return Err(EmptyResults.into());
+        }
+
+        let db = self.aggregate_steps();
+
+        let mut models = Vec::new();
+        for ((pallet, extrinsic), (var_names, data)) in db {
+            let max_vars: Vec<u64> = (0..var_names.len())
+                .map(|index| {
+                    data.keys()
+                        .filter_map(|vars| vars.get(index))
+                        .max()
+                        .cloned()
+                        .unwrap_or(0)
+                })
+                .collect();
+
+            let (xs, ys): (Vec<Vec<f64>>, Vec<f64>) = data
+                .into_iter()
+                .map(|(input_vars, totals)| {
+                    let db_ops = (totals.reads + totals.writes).calc_average(totals.count);
+                    (
+                        input_vars.iter().map(|&var| var as f64).collect(),
+                        config.weight(totals.extrinsic_time.calc_average(totals.count), db_ops),
+                    )
+                })
+                .unzip();
+
+            let coefficients =
+                stats::fit_linear_model(&xs, &ys).ok_or_else(|| UnfittableModel {
+                    pallet: pallet.to_string(),
+                    extrinsic: extrinsic.to_string(),
+                })?;
+
+            models.push(WeightModel {
+                pallet,
+                extrinsic,
+                var_names,
+                base_weight: weights::to_weight(coefficients[0]),
+                component_weights: coefficients[1..]
+                    .iter()
+                    .map(|coefficient| weights::to_weight(*coefficient))
+                    .collect(),
+                max_vars,
+            });
+        }
+
+        Ok(models)
+    }
+    /// Proposes the weights of each extrinsic: a base weight and a weight per unit of
+    /// each component, see `weight_models`.
+    pub fn generate_weight_table(&self, config: &WeightConfig) -> Result<WeightTable<'_>, Error> {
+        let mut table = WeightTable::new();
+
+        for model in self.weight_models(config)? {
+            table.push(WeightTableEntry {
+                pallet: model.pallet,
+                extrinsic: model.extrinsic,
+                base_weight: model.base_weight,
+                component_weights: model
+                    .var_names
+                    .iter()
+                    .map(String::as_str)
+                    .zip(model.component_weights)
+                    .collect(),
+            });
+        }
+
+        Ok(table)
+    }
+    /// Compares the weight functions of an existing weight file with the weights
+    /// implied by the results. Both are evaluated at the highest benchmarked component
+    /// values. Functions are matched by their enclosing module or, if they have none,
+    /// by the given pallet. Extrinsics without a weight function are skipped.
+    ///
+    /// Weights which deviate by less than `tolerance` (in percent) are considered ok.
+    pub fn compare_weights(
+        &self,
+        functions: &[WeightFunction],
+        pallet: Option<&str>,
+        config: &WeightConfig,
+        tolerance: f64,
+    ) -> Result<WeightComparisonTable<'_>, Error> {
+        let mut table = WeightComparisonTable::new();
+
+        for model in self.weight_models(config)? {
+            let function = match weightfile::find_weight_function(
+                functions,
+                pallet,
+                model.pallet,
+                model.extrinsic,
+            ) {
+                Some(function) => function,
+                None => continue,
+            };
+
+            let evaluated_at: Vec<(&str, u64)> = model
+                .var_names
+                .iter()
+                .map(String::as_str)
+                .zip(model.max_vars.iter().cloned())
+                .collect();
+
+            let file_weight = function.weight_at(&evaluated_at, config.db_op_weight);
+            let measured_weight = model.weight_at(&model.max_vars);
+            let percentage = ((file_weight / measured_weight - 1.0) * 100.0).round_by(4);
+
+            table.push(WeightComparisonTableEntry {
+                pallet: model.pallet,
+                extrinsic: model.extrinsic,
+                evaluated_at,
+                file_weight: file_weight.round() as u64,
+                measured_weight: measured_weight.round() as u64,
+                percentage,
+                status: WeightStatus::from_percentage(percentage, tolerance),
+            });
+        }
+
+        Ok(table)
+    }
+    /// Cross-references the components of each extrinsic with the parameters of its
+    /// weight function (see `compare_weights` for how functions are matched). Components
+    /// without a parameter are reported as missing, parameters without a component as
+    /// extra. A missing component and an extra parameter at the same position are
+    /// reported as renamed.
+    pub fn check_components(
+        &self,
+        functions: &[WeightFunction],
+        pallet: Option<&str>,
+    ) -> Result<ComponentCheckTable<'_>, Error> {
+        if self.results.is_empty() {
+            return Err(EmptyResults.into());
+        }
+
+        let mut table = ComponentCheckTable::new();
+
+        for ((result_pallet, extrinsic), (var_names, _)) in self.aggregate_steps() {
+            let mut push = |finding| {
+                table.push(ComponentCheckTableEntry {
+                    pallet: result_pallet,
+                    extrinsic,
+                    finding,
+                })
+            };
+
+            let function =
+                match weightfile::find_weight_function(functions, pallet, result_pallet, extrinsic)
+                {
+                    Some(function) => function,
+                    None => {
+                        push(ComponentFinding::MissingFunction);
+                        continue;
+                    }
+                };
+
+            let params = function.param_names();
+            let mut missing: Vec<(usize, &str)> = var_names
+                .iter()
+                .enumerate()
+                .filter(|(_, name)| !params.contains(&name.as_str()))
+                .map(|(index, name)| (index, name.as_str()))
+                .collect();
+            let mut extra: Vec<(usize, &str)> = params
+                .iter()
+                .enumerate()
+                .filter(|(_, param)| !var_names.iter().any(|name| name == *param))
+                .map(|(index, param)| (index, *param))
+                .collect();
+
+            // Pair up missing components and extra parameters at the same position
+            missing.retain(|(index, name)| {
+                match extra
+                    .iter()
+                    .position(|(param_index, _)| param_index == index)
+                {
+                    Some(position) => {
+                        let (_, param) = extra.remove(position);
+                        push(ComponentFinding::Renamed {
+                            from: param.to_string(),
+                            to: name.to_string(),
+                        });
+                        false
+                    }
+                    None => true,
+                }
+            });
 
-            table.push(new_entry);
+            for (_, name) in missing {
+                push(ComponentFinding::Missing(name.to_string()));
+            }
+            for (_, param) in extra {
+                push(ComponentFinding::Extra(param.to_string()));
+            }
         }
 
         Ok(table)
     }
 }
 
+/// Tests whether the times of two runs differ significantly, see `Significance`.
+fn significance(old: &[f64], new: &[f64]) -> Significance {
+    let round = |value: Option<f64>| value.map(|value| value.round_by(4));
+
+    Significance {
+        t_test_p: round(stats::welch_t_test(old, new)),
+        mann_whitney_p: round(stats::mann_whitney_u(old, new)),
+        cohens_d: round(stats::cohens_d(old, new)),
+        cliffs_delta: round(stats::cliffs_delta(old, new)),
+    }
+}
+
+/// Fitted weight model of an extrinsic, see `ExtrinsicCollection::weight_models`.
+struct WeightModel<'a> {
+    pallet: &'a str,
+    extrinsic: &'a str,
+    var_names: &'a [String],
+    base_weight: u64,
+    component_weights: Vec<u64>,
+    /// Highest benchmarked value of each component.
+    max_vars: Vec<u64>,
+}
+
+impl WeightModel<'_> {
+    /// Evaluates the weight at the given component values.
+    fn weight_at(&self, vars: &[u64]) -> f64 {
+        self.base_weight as f64
+            + self
+                .component_weights
+                .iter()
+                .zip(vars)
+                .map(|(weight, var)| (*weight as f64) * (*var as f64))
+                .sum::<f64>()
+    }
+}
+
+/// Input variable names of an extrinsic and its steps, where each step (input vars)
+/// maps to the summed up measurements of its repeats.
+type StepDb<'a> = (&'a Vec<String>, HashMap<&'a Vec<u64>, StepTotals>);
+
+/// Summed up measurements of all repeats of a step, including the count of repeats
+/// in order to calculate the average.
+#[derive(Debug, Default)]
+struct StepTotals {
+    count: usize,
+    extrinsic_time: u64,
+    storage_root_time: u64,
+    reads: u64,
+    writes: u64,
+}
+
+impl StepTotals {
+    fn add(&mut self, step: &StepRepeatEntry) {
+        self.count += 1;
+        self.extrinsic_time += step.extrinsic_time;
+        self.storage_root_time += step.storage_root_time;
+        self.reads += step.reads;
+        self.writes += step.writes;
+    }
+    fn average_time(&self, metric: Metric) -> f64 {
+        let time = match metric {
+            Metric::ExtrinsicTime => self.extrinsic_time,
+            Metric::StorageRootTime => self.storage_root_time,
+            Metric::TotalTime => self.extrinsic_time + self.storage_root_time,
+        };
+        time.calc_average(self.count)
+    }
+}
+
+/// Builders of results for the unit tests.
+#[cfg(test)]
+mod fixtures {
+    use super::{ExtrinsicResult, StepRepeatEntry};
+
+    /// Result with the component `x`, which has one step per entry of `times`:
+    /// (extrinsic time, storage root time), where `x` is the index of the step.
+    pub(crate) fn result(pallet: &str, extrinsic: &str, times: &[(u64, u64)]) -> ExtrinsicResult {
+        ExtrinsicResult {
+            pallet: pallet.to_string(),
+            extrinsic: extrinsic.to_string(),
+            steps: times.len(),
+            repeats: 1,
+            input_var_names: vec!["x".to_string()],
+            highest_values: vec![],
+            steps_repeats: times
+                .iter()
+                .enumerate()
+                .map(|(x, (extrinsic_time, storage_root_time))| StepRepeatEntry {
+                    input_vars: vec![x as u64],
+                    extrinsic_time: *extrinsic_time,
+                    storage_root_time: *storage_root_time,
+                    ..Default::default()
+                })
+                .collect(),
+            file_hash: 0,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use super::fixtures::result;
     use super::*;
 
+    #[test]
+    fn test_round_by() {
+        assert_eq!(15.1235, 15.123456.round_by(4));
+        assert_eq!(-15.1235, (-15.123456).round_by(4));
+
+        // A small negative change is displayed as `0`, not as `-0`
+        assert!((-0.00001).round_by(4).is_sign_positive());
+        assert_eq!((-0.00001).round_by(4).to_string(), "0");
+        assert_eq!((-0.0).round_by(4).to_string(), "0");
+    }
+
     #[test]
     fn test_calc_average() {
         let total = 20;
@@ -256,4 +1429,335 @@ mod tests {
         assert_eq!(4.0, total.calc_average(5));
         assert_eq!(0.4, total.calc_average(50));
     }
+
+    #[test]
+    fn test_compare_significance_across_steps() {
+        let collection = |times: &[(u64, u64)]| {
+            let mut collection = ExtrinsicCollection::new();
+            let mut result = result("balances", "transfer", &[]);
+            result.steps_repeats = times
+                .iter()
+                .map(|&(x, extrinsic_time)| StepRepeatEntry {
+                    input_vars: vec![x],
+                    extrinsic_time,
+                    ..Default::default()
+                })
+                .collect();
+            collection.push(result);
+            collection
+        };
+
+        // The new run is 5% slower in each step, the repeats barely vary.
+        let old = collection(&[
+            (0, 1000),
+            (0, 1002),
+            (10, 2000),
+            (10, 2002),
+            (20, 3000),
+            (20, 3002),
+        ]);
+        let new = collection(&[
+            (0, 1050),
+            (0, 1052),
+            (10, 2100),
+            (10, 2102),
+            (20, 3150),
+            (20, 3152),
+        ]);
+
+        let table = old.compare(&new, Metric::ExtrinsicTime).unwrap();
+        let significance = table.raw_significance()[0];
+        assert!(significance.is_significant(0.05));
+        assert_eq!(significance.cliffs_delta, Some(1.0));
+
+        // Without the differences between the steps, the times do not overlap.
+        assert_eq!(
+            old.residual_times("balances", "transfer", Metric::ExtrinsicTime),
+            vec![2000.0, 2002.0, 2000.0, 2002.0, 2000.0, 2002.0]
+        );
+    }
+
+    #[test]
+    fn test_check_limits_unrounded_share() {
+        let mut collection = ExtrinsicCollection::new();
+        collection.push(result("balances", "transfer", &[(100_000_400, 0)]));
+
+        let config: LimitsConfig = "block_weight = 1_000_000_000_000\nmax_share = 10.0"
+            .parse()
+            .unwrap();
+        let table = collection.check_limits(&config).unwrap();
+
+        // 10.00004% exceeds the ceiling, even though it is displayed as 10%
+        assert_eq!(table.offender_count(), 1);
+        assert_eq!(table.raw_list()[0].3, 10.0);
+    }
+
+    #[test]
+    fn test_compare_duplicates() {
+        let mut old = ExtrinsicCollection::new();
+        old.push(result("balances", "transfer", &[(100, 0)]));
+        old.push(result("balances", "transfer", &[(300, 0)]));
+        let mut new = ExtrinsicCollection::new();
+        new.push(result("balances", "transfer", &[(220, 0)]));
+
+        // Both modes merge the duplicate results
+        let table = old.compare(&new, Metric::ExtrinsicTime).unwrap();
+        assert_eq!(
+            table.raw_list(),
+            vec![(
+                "balances",
+                "transfer",
+                Some(200.0),
+                Some(220.0),
+                Some(20.0),
+                Some(10.0)
+            )]
+        );
+        let table = old.compare_steps(&new, Metric::ExtrinsicTime).unwrap();
+        assert_eq!(table.raw_list()[0].3, Some(200.0));
+    }
+
+    #[test]
+    fn test_compare_ranks_merged() {
+        let mut old = ExtrinsicCollection::new();
+        old.push(result("balances", "transfer", &[(100, 0)]));
+        old.push(result("balances", "transfer", &[(300, 0)]));
+        old.push(result("balances", "set_balance", &[(400, 0)]));
+        // Only in the old run, faster than all ranked extrinsics
+        old.push(result("system", "remark", &[(50, 0)]));
+        let mut new = ExtrinsicCollection::new();
+        new.push(result("balances", "transfer", &[(220, 0)]));
+        new.push(result("balances", "set_balance", &[(440, 0)]));
+
+        // Duplicates are merged and the ratios only relate ranked extrinsics
+        let table = old.compare_ranks(&new, Metric::ExtrinsicTime).unwrap();
+        assert_eq!(
+            table.raw_list(),
+            vec![
+                ("balances", "transfer", 1.0, 1.0, 0.0, 1.0, 1.0),
+                ("balances", "set_balance", 2.0, 2.0, 0.0, 2.0, 2.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compare_empty_collections() {
+        let empty = ExtrinsicCollection::new();
+        let mut collection = ExtrinsicCollection::new();
+        collection.push(result("balances", "transfer", &[(100, 0)]));
+
+        // Both modes only fail if there is nothing to compare at all
+        assert!(empty.compare(&empty, Metric::ExtrinsicTime).is_err());
+        assert!(empty.compare_steps(&empty, Metric::ExtrinsicTime).is_err());
+
+        let table = empty.compare(&collection, Metric::ExtrinsicTime).unwrap();
+        assert_eq!(table.raw_list().len(), 1);
+        let table = empty
+            .compare_steps(&collection, Metric::ExtrinsicTime)
+            .unwrap();
+        assert!(table.raw_list().is_empty());
+    }
+
+    #[test]
+    fn test_ratio_table_baseline_metric() {
+        let mut collection = ExtrinsicCollection::new();
+        collection.push(result("a", "fast_extrinsic", &[(100, 400), (100, 400)]));
+        collection.push(result("b", "fast_storage_root", &[(200, 100), (200, 100)]));
+
+        let table = collection
+            .generate_ratio_table(&Baseline::Fastest(Metric::ExtrinsicTime))
+            .unwrap();
+        let list = table.raw_list();
+        assert_eq!(
+            list[1],
+            (
+                "b",
+                "fast_storage_root",
+                200.0,
+                100.0,
+                2.0,
+                100.0,
+                0.25,
+                -75.0,
+                300.0,
+                0.6,
+                200.0
+            )
+        );
+
+        let mut table = collection
+            .generate_ratio_table(&Baseline::Fastest(Metric::StorageRootTime))
+            .unwrap();
+        table.sort_by(Metric::StorageRootTime);
+        let list = table.raw_list();
+        assert_eq!(
+            list[0],
+            (
+                "b",
+                "fast_storage_root",
+                200.0,
+                100.0,
+                1.0,
+                0.0,
+                1.0,
+                0.0,
+                300.0,
+                1.0,
+                200.0
+            )
+        );
+        assert_eq!(
+            list[1],
+            (
+                "a",
+                "fast_extrinsic",
+                100.0,
+                400.0,
+                0.5,
+                -50.0,
+                4.0,
+                300.0,
+                500.0,
+                1.6667,
+                100.0
+            )
+        );
+    }
+
+    #[test]
+    fn test_worst_case_time() {
+        let mut res = result("a", "b", &[(300, 10), (100, 10), (200, 10), (300, 30)]);
+        res.steps_repeats[3].input_vars = vec![2];
+
+        // Average of the step with the highest component value (x = 2)
+        assert_eq!(res.worst_case_time(Metric::ExtrinsicTime), 250.0);
+        assert_eq!(res.worst_case_time(Metric::TotalTime), 270.0);
+
+        // Extrapolated to the highest value of the header
+        let mut res = result("a", "b", &[(100, 10), (200, 10), (300, 10)]);
+        res.highest_values = vec![10];
+        assert_eq!(res.worst_case_time(Metric::ExtrinsicTime).round(), 1100.0);
+    }
+
+    #[test]
+    fn test_subtract_overhead() {
+        let mut collection = ExtrinsicCollection::new();
+        // Only the first step (lowest component value) counts as overhead.
+        collection.push(result("system", "remark", &[(10, 20), (500, 500)]));
+        collection.push(result("system", "remark", &[(14, 24)]));
+        collection.push(result("balances", "transfer", &[(100, 100), (110, 10)]));
+
+        collection
+            .subtract_overhead(&"system.remark".parse().unwrap())
+            .unwrap();
+
+        let table = collection
+            .generate_ratio_table(&Baseline::default())
+            .unwrap();
+        let list = table.raw_list();
+        assert_eq!(list.len(), 1);
+        assert_eq!(
+            list[0],
+            ("balances", "transfer", 93.0, 39.0, 1.0, 0.0, 1.0, 0.0, 132.0, 1.0, 98.0)
+        );
+
+        // The overhead was removed from the collection.
+        assert!(collection
+            .subtract_overhead(&"system.remark".parse().unwrap())
+            .is_err());
+
+        // Net times of zero would lead to infinite ratios
+        let mut collection = ExtrinsicCollection::new();
+        collection.push(result("system", "remark", &[(100, 20)]));
+        collection.push(result("balances", "transfer", &[(90, 100), (110, 100)]));
+        collection.push(result("balances", "set_balance", &[(80, 100)]));
+        let err = collection
+            .subtract_overhead(&"system.remark".parse().unwrap())
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "average time of balances.set_balance is zero after subtracting the overhead of system.remark"
+        );
+    }
+
+    #[test]
+    fn test_normalization() {
+        let collections = || {
+            let mut old = ExtrinsicCollection::new();
+            old.push(result("system", "remark", &[(100, 10)]));
+            old.push(result("balances", "transfer", &[(1_000, 100)]));
+            old.push(result("staking", "bond", &[(2_000, 200)]));
+
+            // Twice as slow, and `staking.bond` got more expensive.
+            let mut new = ExtrinsicCollection::new();
+            new.push(result("system", "remark", &[(180, 18)]));
+            new.push(result("balances", "transfer", &[(2_200, 220)]));
+            new.push(result("staking", "bond", &[(4_400, 440)]));
+
+            (old, new)
+        };
+        let bond_time = |collection: &ExtrinsicCollection| {
+            collection
+                .generate_ratio_table(&Baseline::default())
+                .unwrap()
+                .raw_list()[2]
+                .2
+        };
+
+        let (mut old, mut new) = collections();
+        let reference = Normalization::ReferenceSet(vec![
+            "system.remark".parse().unwrap(),
+            "balances.transfer".parse().unwrap(),
+        ]);
+        // Geometric mean of 1.8 and 2.2
+        let factor = reference
+            .apply(&mut old, &mut new, Metric::ExtrinsicTime)
+            .unwrap();
+        assert_eq!(factor, 1.9900);
+        assert_eq!(bond_time(&old), 2_000.0);
+        assert_eq!(bond_time(&new), 2_211.0);
+
+        let (mut old, mut new) = collections();
+        let factors = Normalization::SpeedFactors(0.5, 1.0);
+        let factor = factors
+            .apply(&mut old, &mut new, Metric::ExtrinsicTime)
+            .unwrap();
+        assert_eq!(factor, 2.0);
+        assert_eq!(bond_time(&old), 4_000.0);
+        assert_eq!(bond_time(&new), 4_400.0);
+
+        let (mut old, mut new) = collections();
+        assert!(Normalization::SpeedFactors(0.0, 1.0)
+            .apply(&mut old, &mut new, Metric::ExtrinsicTime)
+            .is_err());
+        assert!(Normalization::ReferenceSet(vec![])
+            .apply(&mut old, &mut new, Metric::ExtrinsicTime)
+            .is_err());
+        assert!(
+            Normalization::ReferenceSet(vec!["system.unknown".parse().unwrap()])
+                .apply(&mut old, &mut new, Metric::ExtrinsicTime)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_extrinsic_id() {
+        let id: ExtrinsicId = "balances.transfer".parse().unwrap();
+        assert_eq!(id.pallet, "balances");
+        assert_eq!(id.extrinsic, "transfer");
+        assert_eq!(id.to_string(), "balances.transfer");
+
+        assert!("balances".parse::<ExtrinsicId>().is_err());
+        assert!(".transfer".parse::<ExtrinsicId>().is_err());
+        assert!("balances.".parse::<ExtrinsicId>().is_err());
+    }
+
+    #[test]
+    fn test_cmp_components() {
+        assert_eq!(cmp_components(&[1, 1000], &[199, 1000]), Ordering::Less);
+        assert_eq!(cmp_components(&[1000, 1], &[2, 1]), Ordering::Greater);
+        // Equal sums are resolved by the values in order
+        assert_eq!(cmp_components(&[1, 1000], &[1000, 1]), Ordering::Less);
+        assert_eq!(cmp_components(&[5], &[5]), Ordering::Equal);
+    }
 }
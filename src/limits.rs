@@ -0,0 +1,131 @@
+//! Weight ceilings of the extrinsics, used for failing CI when an extrinsic
+//! takes up too much of a block.
+
+use crate::WeightConfig;
+use failure::Error;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Ceilings as configured in a TOML file, e.g.
+///
+/// ```toml
+/// # Block limit, either as weight or as time (in seconds)
+/// block_weight = 2_000_000_000_000
+/// # Weight of a database operation, counted at the highest components
+/// db_op_weight = 25_000_000
+/// # Default ceiling of each extrinsic, in percent of the block limit
+/// max_share = 10.0
+///
+/// [pallets]
+/// democracy = 25.0
+///
+/// [extrinsics]
+/// "balances.transfer" = 1.0
+/// ```
+///
+/// The ceiling of an extrinsic takes precedence over the one of its pallet,
+/// which takes precedence over `max_share`. Extrinsics without any ceiling are
+/// not checked.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LimitsConfig {
+    /// Weight limit of a block. Replaces `block_time` if specified.
+    pub block_weight: Option<f64>,
+    /// Execution time available per block, in seconds (default: 2).
+    pub block_time: Option<f64>,
+    /// Weight per nanosecond of extrinsic time (default: 1000).
+    pub weight_per_nanos: Option<f64>,
+    /// Multiplier which is applied to the extrinsic time (default: 1).
+    pub safety_multiplier: Option<f64>,
+    /// Weight of a single database operation (default: 0).
+    pub db_op_weight: Option<f64>,
+    pub max_share: Option<f64>,
+    #[serde(default)]
+    pub pallets: HashMap<String, f64>,
+    /// Ceilings per extrinsic, keyed by `pallet.extrinsic`.
+    #[serde(default)]
+    pub extrinsics: HashMap<String, f64>,
+}
+
+impl LimitsConfig {
+    /// Reads and parses the config file at the given path.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+    pub(crate) fn weight_per_nanos(&self) -> f64 {
+        self.weight_per_nanos.unwrap_or(1_000.0)
+    }
+    /// Converts times and database operations into weight, like the *weights* review.
+    pub(crate) fn weight_config(&self) -> WeightConfig {
+        let default = WeightConfig::default();
+        WeightConfig {
+            weight_per_nanos: self.weight_per_nanos(),
+            safety_multiplier: self.safety_multiplier.unwrap_or(default.safety_multiplier),
+            db_op_weight: self.db_op_weight.unwrap_or(default.db_op_weight),
+        }
+    }
+    /// Weight limit of a block.
+    pub(crate) fn block_weight(&self) -> f64 {
+        self.block_weight.unwrap_or_else(|| {
+            self.block_time.unwrap_or(2.0) * 1_000_000_000.0 * self.weight_per_nanos()
+        })
+    }
+    /// Ceiling of the given extrinsic, in percent of the block limit.
+    pub(crate) fn ceiling(&self, pallet: &str, extrinsic: &str) -> Option<f64> {
+        self.extrinsics
+            .get(&format!("{}.{}", pallet, extrinsic))
+            .or_else(|| self.pallets.get(pallet))
+            .cloned()
+            .or(self.max_share)
+    }
+}
+
+impl std::str::FromStr for LimitsConfig {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ceiling() {
+        let config: LimitsConfig = r#"
+            max_share = 10.0
+
+            [pallets]
+            democracy = 25.0
+
+            [extrinsics]
+            "democracy.delegate" = 30.0
+            "balances.transfer" = 1.0
+        "#
+        .parse()
+        .unwrap();
+
+        assert_eq!(config.ceiling("balances", "transfer"), Some(1.0));
+        assert_eq!(config.ceiling("balances", "set_balance"), Some(10.0));
+        assert_eq!(config.ceiling("democracy", "propose"), Some(25.0));
+        assert_eq!(config.ceiling("democracy", "delegate"), Some(30.0));
+
+        // 2 seconds at 1000 weight per nanosecond
+        assert_eq!(config.block_weight(), 2_000_000_000_000.0);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!("max_shar = 10.0".parse::<LimitsConfig>().is_err());
+        assert!("max_share = \"10%\"".parse::<LimitsConfig>().is_err());
+
+        let config: LimitsConfig = "block_time = 1.5\nweight_per_nanos = 2.0".parse().unwrap();
+        assert_eq!(config.block_weight(), 3_000_000_000.0);
+        assert_eq!(config.ceiling("balances", "transfer"), None);
+        assert_eq!(config.weight_config().safety_multiplier, 1.0);
+        assert_eq!(config.weight_config().db_op_weight, 0.0);
+    }
+}
@@ -1,10 +1,37 @@
-use clap::{App, Arg, SubCommand};
+#[macro_use]
+extern crate clap;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
 use failure::Error;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use libreview::{ExtrinsicCollection, FileScraper};
+use libreview::baseline::{self, DEFAULT_BASELINE_FILE};
+use libreview::codegen::{self, DEFAULT_TEMPLATE};
+use libreview::history::{self, DetectionConfig, DEFAULT_HISTORY_DIR};
+use libreview::notify::Payload;
+use libreview::review::DEFAULT_LEDGER_FILE;
+use libreview::tables::RowHighlighter;
+use libreview::watch::ResultWatcher;
+use libreview::weightfile;
+use libreview::{
+    Baseline, BaselineConfig, BaselineSnapshot, CapacityConfig, ExtrinsicCollection, ExtrinsicId,
+    FileScraper, GitSource, HistoryStore, LimitsConfig, Metric, Normalization, NotifyConfig,
+    ReviewLedger, RunMetadata, StepBaseline, WeightConfig,
+};
 
 fn build_collection(path: &str, skip_warn: bool) -> Result<ExtrinsicCollection, Error> {
+    read_collection(path, skip_warn).map(|(collection, _)| collection)
+}
+
+/// Like `build_collection`, but additionally returns the full hash of the commit the
+/// results were read from, if `path` is a git source.
+fn read_collection(
+    path: &str,
+    skip_warn: bool,
+) -> Result<(ExtrinsicCollection, Option<String>), Error> {
     let scraper = FileScraper::new(path)?;
+    let commit = scraper.commit().map(|commit| commit.to_string());
     let mut collection = ExtrinsicCollection::new();
 
     for result in scraper {
@@ -12,20 +39,334 @@ fn build_collection(path: &str, skip_warn: bool) -> Result<ExtrinsicCollection,
             .parse()
             .map(|result| {
                 collection.push(result);
-                ()
             })
             .map_err(|err| {
                 if !skip_warn {
-                    eprintln!("Warn: {}", err.to_string());
+                    eprintln!("Warn: {}", err);
                 }
-                ()
             });
     }
 
-    Ok(collection)
+    Ok((collection, commit))
+}
+
+/// Prints the commits the compared runs were read from, if any, ahead of the table:
+/// as `#` comments for CSV, as Markdown for `--markdown`.
+fn print_commits(matches: &ArgMatches, old: Option<&str>, new: Option<&str>) {
+    let commits: Vec<(&str, &str)> = [("Old", old), ("New", new)]
+        .iter()
+        .filter_map(|(run, commit)| commit.map(|commit| (*run, commit)))
+        .collect();
+    if commits.is_empty() {
+        return;
+    }
+
+    for (run, commit) in commits {
+        if matches.is_present("csv") {
+            println!("# {} results: commit {}", run, commit);
+        } else if matches.is_present("markdown") {
+            println!("- {} results: commit `{}`", run, commit);
+        } else {
+            println!("{} results: commit {}", run, commit);
+        }
+    }
+    if !matches.is_present("csv") {
+        println!();
+    }
+}
+
+/// Subtracts the overhead extrinsic from the collection, if specified.
+fn apply_overhead(collection: &mut ExtrinsicCollection, value: Option<&str>) -> Result<(), Error> {
+    if let Some(overhead) = value {
+        collection.subtract_overhead(&overhead.parse::<ExtrinsicId>()?)?;
+    }
+
+    Ok(())
+}
+
+/// Argument which accepts the overhead extrinsic, see `apply_overhead`.
+fn overhead_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("overhead")
+        .long("overhead")
+        .takes_value(true)
+        .value_name("PALLET.EXTRINSIC")
+        .help("Empty extrinsic whose average is subtracted from all results, e.g. 'system.remark'")
 }
 
-fn main() -> Result<(), Error> {
+/// Arguments for converting times into weights, see `parse_weight_config`.
+fn weight_args<'a, 'b>() -> Vec<Arg<'a, 'b>> {
+    vec![
+        Arg::with_name("weight-per-nanos")
+            .long("weight-per-nanos")
+            .takes_value(true)
+            .help("Weight per nanosecond of extrinsic time (default: 1000)"),
+        Arg::with_name("safety-multiplier")
+            .long("safety-multiplier")
+            .takes_value(true)
+            .help("Multiplier applied to the measured times, e.g. 1.2 (default: 1)"),
+        Arg::with_name("db-op-weight")
+            .long("db-op-weight")
+            .takes_value(true)
+            .help("Weight of a single database read or write (default: 0)"),
+    ]
+}
+
+/// Parses the weight arguments, missing values are set to their defaults.
+fn parse_weight_config(matches: &ArgMatches) -> Result<WeightConfig, Error> {
+    let mut config = WeightConfig::default();
+
+    if matches.is_present("weight-per-nanos") {
+        config.weight_per_nanos = value_t!(matches, "weight-per-nanos", f64)?;
+    }
+    if matches.is_present("safety-multiplier") {
+        config.safety_multiplier = value_t!(matches, "safety-multiplier", f64)?;
+    }
+    if matches.is_present("db-op-weight") {
+        config.db_op_weight = value_t!(matches, "db-op-weight", f64)?;
+    }
+
+    Ok(config)
+}
+
+/// Argument which accepts one of the metrics, see `parse_metric`.
+fn metric_arg<'a, 'b>(name: &'a str, help: &'b str) -> Arg<'a, 'b> {
+    Arg::with_name(name)
+        .long(name)
+        .takes_value(true)
+        .possible_values(&["extrinsic", "storage-root", "total"])
+        .help(help)
+}
+
+/// Parses the value of a metric argument. Defaults to the extrinsic time.
+fn parse_metric(value: Option<&str>) -> Metric {
+    match value {
+        Some("storage-root") => Metric::StorageRootTime,
+        Some("total") => Metric::TotalTime,
+        _ => Metric::ExtrinsicTime,
+    }
+}
+
+/// Parses the `--baseline` value of the per-step review: `fastest`, `lowest`
+/// or the name of a component (e.g. `u`). The fastest step is selected based
+/// on the given metric.
+fn parse_step_baseline(value: Option<&str>, metric: Metric) -> StepBaseline {
+    match value {
+        None | Some("fastest") => StepBaseline::Fastest(metric),
+        Some("lowest") => StepBaseline::LowestComponents,
+        Some(component) => StepBaseline::Component(component.to_string()),
+    }
+}
+
+/// Parses the block limits of the capacity report. The block weight limit, if
+/// specified, replaces the block time.
+fn parse_capacity_config(matches: &ArgMatches) -> Result<CapacityConfig, Error> {
+    let mut config = CapacityConfig::default();
+
+    if matches.is_present("normal-ratio") {
+        config.normal_ratio = value_t!(matches, "normal-ratio", f64)? / 100.0;
+    }
+    if matches.is_present("block-time") {
+        config.block_time_nanos = value_t!(matches, "block-time", f64)? * 1_000_000_000.0;
+    }
+    if matches.is_present("block-weight") {
+        config = CapacityConfig::from_block_weight(
+            value_t!(matches, "block-weight", f64)?,
+            parse_weight_config(matches)?.weight_per_nanos,
+            config.normal_ratio,
+        );
+    }
+
+    Ok(config)
+}
+
+/// Flag which re-renders the table whenever the result files change, see `watch`.
+fn watch_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("watch")
+        .long("watch")
+        .conflicts_with("csv")
+        .help("Re-renders the table whenever the result files change")
+}
+
+/// Renders the results of `paths` and renders them again whenever a file changes,
+/// highlighting the rows which changed. Only the changed files are parsed again.
+/// The collections are passed in the order of the paths. Changes of the additional
+/// `files` (e.g. a weights file) trigger a rendering as well. Runs until interrupted.
+fn watch<F>(paths: &[&str], files: &[&str], skip_warn: bool, mut render: F) -> Result<(), Error>
+where
+    F: FnMut(&mut [ExtrinsicCollection], Option<&mut RowHighlighter>) -> Result<(), Error>,
+{
+    if paths.iter().any(|path| GitSource::is_git_source(path)) {
+        return Err(failure::err_msg("--watch does not support git sources"));
+    }
+
+    let mut watchers: Vec<ResultWatcher> = paths.iter().map(ResultWatcher::new).collect();
+    let mut modified: Vec<Option<SystemTime>> = vec![None; files.len()];
+    let mut highlighter = RowHighlighter::default();
+
+    loop {
+        let mut changed = 0;
+        for watcher in &mut watchers {
+            changed += watcher.poll()?;
+        }
+        for (file, modified) in files.iter().zip(&mut modified) {
+            let current = std::fs::metadata(file)
+                .and_then(|metadata| metadata.modified())
+                .ok();
+            if current != *modified {
+                *modified = current;
+                changed += 1;
+            }
+        }
+
+        if changed > 0 {
+            // Clear the screen
+            print!("\x1B[2J\x1B[H");
+
+            if !skip_warn {
+                for warning in watchers.iter().flat_map(|watcher| watcher.warnings()) {
+                    eprintln!("Warn: {}", warning);
+                }
+            }
+
+            let mut collections: Vec<ExtrinsicCollection> = watchers
+                .iter()
+                .map(|watcher| watcher.collection())
+                .collect();
+            // Keep watching, the error might be fixed by the next change
+            if let Err(err) = render(&mut collections, Some(&mut highlighter)) {
+                eprintln!("Error: {}", err);
+            }
+
+            println!(
+                "Watching {} for changes, press Ctrl+C to stop.",
+                paths
+                    .iter()
+                    .chain(files)
+                    .cloned()
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            );
+        }
+
+        thread::sleep(Duration::from_secs(1));
+    }
+}
+
+/// Renders the collection of `PATH`, or watches it if `--watch` is specified. The
+/// `WEIGHTS_FILE` of the subcommand, if any, is watched as well.
+fn render_path<F>(matches: &ArgMatches, mut render: F) -> Result<(), Error>
+where
+    F: FnMut(&mut ExtrinsicCollection, Option<&mut RowHighlighter>) -> Result<(), Error>,
+{
+    // Unwrapping is ok, since "PATH" is set to required
+    let path = matches.value_of("PATH").unwrap();
+    let skip_warn = matches.is_present("skip-warnings");
+
+    if matches.is_present("watch") {
+        let files: Vec<&str> = matches.value_of("WEIGHTS_FILE").into_iter().collect();
+        watch(&[path], &files, skip_warn, |collections, highlighter| {
+            render(&mut collections[0], highlighter)
+        })
+    } else {
+        render(&mut build_collection(path, skip_warn)?, None)
+    }
+}
+
+/// Argument which accepts the baseline file.
+fn baseline_file_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("file")
+        .long("file")
+        .takes_value(true)
+        .value_name("FILE")
+        .help("Baseline file (default: bench-baseline.json)")
+}
+
+/// Argument which accepts the extrinsics of the reference set, see `Normalization`.
+fn reference_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("reference")
+        .long("reference")
+        .takes_value(true)
+        .multiple(true)
+        .number_of_values(1)
+        .value_name("PALLET.EXTRINSIC")
+        .help("Extrinsic whose code did not change, its change of time is attributed to the hardware. Can be repeated")
+}
+
+/// Arguments which accept the speed factors of the machines of two runs.
+fn speed_factor_args<'a, 'b>() -> [Arg<'a, 'b>; 2] {
+    [
+        Arg::with_name("old-speed-factor")
+            .long("old-speed-factor")
+            .takes_value(true)
+            .conflicts_with("reference")
+            .help("Speed factor of the old machine, e.g. 1.25 if it takes 25% longer than the reference machine (default: 1)"),
+        Arg::with_name("new-speed-factor")
+            .long("new-speed-factor")
+            .takes_value(true)
+            .conflicts_with("reference")
+            .help("Speed factor of the new machine (default: 1)"),
+    ]
+}
+
+/// Parses the extrinsics of a repeatable argument.
+fn parse_ids(matches: &ArgMatches, name: &str) -> Result<Vec<ExtrinsicId>, Error> {
+    matches
+        .values_of(name)
+        .map(|values| values.map(|id| id.parse()).collect())
+        .unwrap_or_else(|| Ok(vec![]))
+}
+
+/// Parses the normalization of two runs, based on `reference_arg` and `speed_factor_args`.
+fn parse_normalization(matches: &ArgMatches) -> Result<Option<Normalization>, Error> {
+    let reference = parse_ids(matches, "reference")?;
+    if !reference.is_empty() {
+        return Ok(Some(Normalization::ReferenceSet(reference)));
+    }
+
+    if !matches.is_present("old-speed-factor") && !matches.is_present("new-speed-factor") {
+        return Ok(None);
+    }
+
+    let factor = |name| -> Result<f64, Error> {
+        if matches.is_present(name) {
+            Ok(value_t!(matches, name, f64)?)
+        } else {
+            Ok(1.0)
+        }
+    };
+
+    Ok(Some(Normalization::SpeedFactors(
+        factor("old-speed-factor")?,
+        factor("new-speed-factor")?,
+    )))
+}
+
+/// Argument which accepts the review ledger.
+fn ledger_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("ledger")
+        .long("ledger")
+        .takes_value(true)
+        .value_name("FILE")
+        .help("Review ledger (default: bench-reviews.json)")
+}
+
+/// Argument which accepts the directory of the run history.
+fn history_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("history")
+        .long("history")
+        .takes_value(true)
+        .value_name("DIR")
+        .help("Directory of the recorded runs (default: .bench-history)")
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<(), Error> {
     let matches = App::new("bench-review")
         .version("1.0")
         .author("Fabio Lama <github.com/lamafab>")
@@ -33,43 +374,990 @@ fn main() -> Result<(), Error> {
         .subcommand(
             SubCommand::with_name("per-extrinsic")
                 .arg(Arg::with_name("PATH").required(true))
+                .arg(metric_arg("sort-by", "Metric to sort by"))
+                .arg(
+                    Arg::with_name("rank-by-worst-case")
+                        .long("rank-by-worst-case")
+                        .conflicts_with("sort-by")
+                        .help("Sorts by the worst case extrinsic time instead of the average"),
+                )
+                .arg(metric_arg(
+                    "baseline-metric",
+                    "Metric used to select the fastest extrinsic",
+                ))
+                .arg(
+                    Arg::with_name("baseline")
+                        .long("baseline")
+                        .takes_value(true)
+                        .value_name("PALLET.EXTRINSIC")
+                        .conflicts_with("baseline-metric")
+                        .help("Reference extrinsic used as the baseline, e.g. 'balances.transfer'"),
+                )
+                .arg(overhead_arg())
+                .arg(
+                    Arg::with_name("ledger")
+                        .long("ledger")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("Review ledger, adds the review status of each extrinsic"),
+                )
                 .arg(Arg::with_name("csv").long("csv"))
+                .arg(watch_arg())
                 .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
         )
         .subcommand(
             SubCommand::with_name("per-step")
                 .arg(Arg::with_name("PATH").required(true))
+                .arg(
+                    Arg::with_name("baseline")
+                        .long("baseline")
+                        .takes_value(true)
+                        .help("Baseline step: 'fastest' (default), 'lowest' or a component name"),
+                )
+                .arg(
+                    Arg::with_name("group-by-component")
+                        .long("group-by-component")
+                        .conflicts_with_all(&["baseline", "sort-by", "baseline-metric"])
+                        .help("Groups the steps by the component which varies"),
+                )
+                .arg(metric_arg("sort-by", "Metric to sort by"))
+                .arg(metric_arg(
+                    "baseline-metric",
+                    "Metric used to select the fastest step",
+                ))
+                .arg(overhead_arg())
+                .arg(Arg::with_name("csv").long("csv"))
+                .arg(watch_arg())
+                .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+        )
+        .subcommand(
+            SubCommand::with_name("weights")
+                .about("Proposes the base weight and per-component weights of each extrinsic")
+                .arg(Arg::with_name("PATH").required(true))
+                .args(&weight_args())
+                .arg(overhead_arg())
+                .arg(Arg::with_name("csv").long("csv"))
+                .arg(watch_arg())
+                .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+        )
+        .subcommand(
+            SubCommand::with_name("generate-weights")
+                .about("Generates a Rust module with the weight function of each extrinsic")
+                .arg(Arg::with_name("PATH").required(true))
+                .args(&weight_args())
+                .arg(overhead_arg())
+                .arg(
+                    Arg::with_name("template")
+                        .long("template")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("Template file, replaces the built-in template"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("Writes the module to the file instead of stdout"),
+                )
+                .arg(watch_arg())
+                .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+        )
+        .subcommand(
+            SubCommand::with_name("compare-weights")
+                .about("Compares the weights of an existing weight file with the benchmark results")
+                .arg(Arg::with_name("PATH").required(true))
+                .arg(Arg::with_name("WEIGHTS_FILE").required(true))
+                .arg(
+                    Arg::with_name("pallet")
+                        .long("pallet")
+                        .takes_value(true)
+                        .help("Pallet of the weight functions which are not within a module"),
+                )
+                .arg(
+                    Arg::with_name("tolerance")
+                        .long("tolerance")
+                        .takes_value(true)
+                        .help(
+                            "Deviation (in %) up to which weights are considered ok (default: 10)",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("all")
+                        .long("all")
+                        .help("Also shows the weights which are within the tolerance"),
+                )
+                .args(&weight_args())
+                .arg(overhead_arg())
+                .arg(Arg::with_name("csv").long("csv"))
+                .arg(watch_arg())
+                .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+        )
+        .subcommand(
+            SubCommand::with_name("check-components")
+                .about("Checks the components against the parameters of the weight functions")
+                .arg(Arg::with_name("PATH").required(true))
+                .arg(Arg::with_name("WEIGHTS_FILE").required(true))
+                .arg(
+                    Arg::with_name("pallet")
+                        .long("pallet")
+                        .takes_value(true)
+                        .help("Pallet of the weight functions which are not within a module"),
+                )
+                .arg(Arg::with_name("csv").long("csv"))
+                .arg(watch_arg())
+                .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+        )
+        .subcommand(
+            SubCommand::with_name("capacity")
+                .about("Projects how many calls of each extrinsic fit into a block")
+                .arg(Arg::with_name("PATH").required(true))
+                .arg(
+                    Arg::with_name("block-time")
+                        .long("block-time")
+                        .takes_value(true)
+                        .value_name("SECONDS")
+                        .help("Execution time available per block (default: 2)"),
+                )
+                .arg(
+                    Arg::with_name("block-weight")
+                        .long("block-weight")
+                        .takes_value(true)
+                        .conflicts_with("block-time")
+                        .help("Weight limit per block, replaces the block time"),
+                )
+                .arg(
+                    Arg::with_name("weight-per-nanos")
+                        .long("weight-per-nanos")
+                        .takes_value(true)
+                        .requires("block-weight")
+                        .help("Weight per nanosecond of extrinsic time (default: 1000)"),
+                )
+                .arg(
+                    Arg::with_name("normal-ratio")
+                        .long("normal-ratio")
+                        .takes_value(true)
+                        .value_name("PERCENT")
+                        .help("Share of the block available to normal dispatches (default: 75)"),
+                )
+                .arg(metric_arg("metric", "Metric used as the time of a call"))
+                .arg(overhead_arg())
+                .arg(Arg::with_name("csv").long("csv"))
+                .arg(watch_arg())
+                .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+        )
+        .subcommand(
+            SubCommand::with_name("check-limits")
+                .about("Fails if the worst case weight of an extrinsic exceeds its ceiling")
+                .arg(Arg::with_name("PATH").required(true))
+                .arg(
+                    Arg::with_name("config")
+                        .long("config")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .required(true)
+                        .help("TOML file with the block limit and the ceilings"),
+                )
+                .arg(
+                    Arg::with_name("junit")
+                        .long("junit")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .help("Writes the checks as JUnit XML to the file"),
+                )
+                .arg(overhead_arg())
+                .arg(Arg::with_name("csv").long("csv"))
+                .arg(watch_arg())
+                .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+        )
+        .subcommand(
+            SubCommand::with_name("compare")
+                .about("Compares the average times of two benchmark runs")
+                .arg(Arg::with_name("OLD_PATH").required(true))
+                .arg(Arg::with_name("NEW_PATH").required(true))
+                .arg(metric_arg("metric", "Metric to compare"))
+                .arg(
+                    Arg::with_name("per-step")
+                        .long("per-step")
+                        .help("Compares each step and the marginal cost of each component"),
+                )
+                .arg(
+                    Arg::with_name("significant-only")
+                        .long("significant-only")
+                        .help("Only lists the changes which are statistically significant"),
+                )
+                .arg(
+                    Arg::with_name("alpha")
+                        .long("alpha")
+                        .takes_value(true)
+                        .help("Significance level of the tests (default: 0.05)"),
+                )
+                .arg(overhead_arg())
+                .arg(reference_arg())
+                .args(&speed_factor_args())
+                .arg(Arg::with_name("csv").long("csv"))
+                .arg(
+                    Arg::with_name("markdown")
+                        .long("markdown")
+                        .conflicts_with("csv")
+                        .help("Prints the table as Markdown"),
+                )
+                .arg(watch_arg().conflicts_with("markdown"))
+                .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+        )
+        .subcommand(
+            SubCommand::with_name("rank-stability")
+                .about("Compares the ordering of the extrinsics of two benchmark runs")
+                .arg(Arg::with_name("OLD_PATH").required(true))
+                .arg(Arg::with_name("NEW_PATH").required(true))
+                .arg(metric_arg("metric", "Metric to rank by"))
+                .arg(
+                    Arg::with_name("top")
+                        .long("top")
+                        .takes_value(true)
+                        .value_name("COUNT")
+                        .help("Amount of extrinsics with the largest rank movements to list (default: 10)"),
+                )
+                .arg(overhead_arg())
+                .arg(Arg::with_name("csv").long("csv"))
+                .arg(watch_arg())
+                .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+        )
+        .subcommand(
+            SubCommand::with_name("record")
+                .about("Records a benchmark run in the local history")
+                .arg(Arg::with_name("PATH").required(true))
+                .arg(
+                    Arg::with_name("label")
+                        .long("label")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of the run, e.g. the benchmarked commit"),
+                )
+                .arg(
+                    Arg::with_name("commit")
+                        .long("commit")
+                        .takes_value(true)
+                        .help("Hash of the benchmarked commit (default: the commit of a git source)"),
+                )
+                .arg(
+                    Arg::with_name("machine")
+                        .long("machine")
+                        .takes_value(true)
+                        .help("Machine the benchmarks were run on"),
+                )
+                .arg(
+                    Arg::with_name("runtime")
+                        .long("runtime")
+                        .takes_value(true)
+                        .help("Benchmarked runtime, e.g. 'polkadot-9050'"),
+                )
+                .arg(
+                    Arg::with_name("speed-factor")
+                        .long("speed-factor")
+                        .takes_value(true)
+                        .help("Speed factor of the machine, e.g. 1.25 if it takes 25% longer than the reference machine"),
+                )
+                .arg(history_arg())
+                .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+        )
+        .subcommand(
+            SubCommand::with_name("history")
+                .about("Shows the trend of an extrinsic over the recorded runs")
+                .arg(
+                    Arg::with_name("EXTRINSIC")
+                        .required(true)
+                        .help("Extrinsic to show, e.g. 'balances.transfer'"),
+                )
+                .arg(metric_arg("metric", "Metric to show"))
+                .arg(history_arg())
+                .arg(overhead_arg())
+                .arg(reference_arg())
+                .arg(Arg::with_name("csv").long("csv"))
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .conflicts_with("csv")
+                        .help("Prints the trend as JSON"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("detect-changes")
+                .about("Detects level shifts and anomalous runs in the recorded runs")
+                .arg(
+                    Arg::with_name("threshold")
+                        .long("threshold")
+                        .takes_value(true)
+                        .help("Standard deviations of the noise a change must exceed (default: 3)"),
+                )
+                .arg(
+                    Arg::with_name("min-change")
+                        .long("min-change")
+                        .takes_value(true)
+                        .value_name("PERCENT")
+                        .help("Smallest change which is reported (default: 5)"),
+                )
+                .arg(metric_arg("metric", "Metric to analyse"))
+                .arg(history_arg())
+                .arg(overhead_arg())
+                .arg(reference_arg())
+                .arg(Arg::with_name("csv").long("csv")),
+        )
+        .subcommand(
+            SubCommand::with_name("notify")
+                .about("Posts the regressions between two benchmark runs to a webhook")
+                .arg(Arg::with_name("OLD_PATH").required(true))
+                .arg(Arg::with_name("NEW_PATH").required(true))
+                .arg(
+                    Arg::with_name("config")
+                        .long("config")
+                        .takes_value(true)
+                        .value_name("FILE")
+                        .required(true)
+                        .help("TOML file with the webhook and the regression rules"),
+                )
+                .arg(
+                    Arg::with_name("dry-run")
+                        .long("dry-run")
+                        .help("Prints the payload instead of posting it"),
+                )
+                .arg(metric_arg("metric", "Metric to compare"))
+                .arg(overhead_arg())
+                .arg(reference_arg())
+                .args(&speed_factor_args())
+                .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+        )
+        .subcommand(
+            SubCommand::with_name("baseline")
+                .about("Accepts a run as the baseline and checks later runs against it")
+                .subcommand(
+                    SubCommand::with_name("accept")
+                        .about("Writes the statistics of the run to the baseline file")
+                        .arg(Arg::with_name("PATH").required(true))
+                        .arg(baseline_file_arg())
+                        .arg(metric_arg("metric", "Metric to record"))
+                        .arg(overhead_arg())
+                        .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+                )
+                .subcommand(
+                    SubCommand::with_name("check")
+                        .about("Fails if the run deviates from the baseline without explanation")
+                        .arg(Arg::with_name("PATH").required(true))
+                        .arg(baseline_file_arg())
+                        .arg(
+                            Arg::with_name("config")
+                                .long("config")
+                                .takes_value(true)
+                                .value_name("FILE")
+                                .help("TOML file with the tolerances and the accepted deviations"),
+                        )
+                        .arg(
+                            Arg::with_name("tolerance")
+                                .long("tolerance")
+                                .takes_value(true)
+                                .help("Deviation (in %) which is ok, replaces the one of the config (default: 10)"),
+                        )
+                        .arg(Arg::with_name("csv").long("csv"))
+                        .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("sign-off")
+                .about("Records the approval of the extrinsics in the review ledger")
+                .arg(Arg::with_name("PATH").required(true))
+                .arg(
+                    Arg::with_name("reviewer")
+                        .long("reviewer")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Name of the reviewer"),
+                )
+                .arg(
+                    Arg::with_name("extrinsic")
+                        .long("extrinsic")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .value_name("PALLET.EXTRINSIC")
+                        .help("Extrinsic to approve, can be repeated (default: all)"),
+                )
+                .arg(ledger_arg())
+                .arg(overhead_arg())
+                .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
+        )
+        .subcommand(
+            SubCommand::with_name("reviews")
+                .about("Lists which extrinsics are approved, changed since approval or never reviewed")
+                .arg(Arg::with_name("PATH").required(true))
+                .arg(ledger_arg())
+                .arg(overhead_arg())
                 .arg(Arg::with_name("csv").long("csv"))
                 .arg(Arg::with_name("skip-warnings").long("skip-warnings")),
         )
         .get_matches();
 
     if let Some(matches) = matches.subcommand_matches("per-extrinsic") {
+        render_path(matches, |collection, highlighter| {
+            apply_overhead(collection, matches.value_of("overhead"))?;
+
+            let baseline = match matches.value_of("baseline") {
+                Some(reference) => Baseline::Reference(reference.parse()?),
+                None => Baseline::Fastest(parse_metric(matches.value_of("baseline-metric"))),
+            };
+            let mut table = collection.generate_ratio_table(&baseline)?;
+            if matches.is_present("rank-by-worst-case") {
+                table.sort_by_worst_case();
+            } else {
+                table.sort_by(parse_metric(matches.value_of("sort-by")));
+            }
+            if let Some(path) = matches.value_of("ledger") {
+                ReviewLedger::open(path)?.mark(collection, &mut table);
+            }
+
+            if let Some(highlighter) = highlighter {
+                table.print_highlighted(highlighter);
+            } else if matches.is_present("csv") {
+                table.print_csv();
+            } else {
+                table.print();
+            }
+
+            Ok(())
+        })?;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("per-step") {
+        render_path(matches, |collection, highlighter| {
+            apply_overhead(collection, matches.value_of("overhead"))?;
+
+            if matches.is_present("group-by-component") {
+                let mut table = collection.generate_component_table()?;
+                table.sort_by_component();
+
+                if let Some(highlighter) = highlighter {
+                    table.print_highlighted(highlighter);
+                } else if matches.is_present("csv") {
+                    table.print_csv();
+                } else {
+                    table.print();
+                }
+
+                return Ok(());
+            }
+
+            let baseline = parse_step_baseline(
+                matches.value_of("baseline"),
+                parse_metric(matches.value_of("baseline-metric")),
+            );
+            let mut table = collection.generate_step_table(&baseline)?;
+            table.sort_by(parse_metric(matches.value_of("sort-by")));
+
+            if let Some(highlighter) = highlighter {
+                table.print_highlighted(highlighter);
+            } else if matches.is_present("csv") {
+                table.print_csv();
+            } else {
+                table.print();
+            }
+
+            Ok(())
+        })?;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("weights") {
+        let config = parse_weight_config(matches)?;
+
+        render_path(matches, |collection, highlighter| {
+            apply_overhead(collection, matches.value_of("overhead"))?;
+
+            let mut table = collection.generate_weight_table(&config)?;
+            table.sort_by_extrinsic();
+
+            if let Some(highlighter) = highlighter {
+                table.print_highlighted(highlighter);
+            } else if matches.is_present("csv") {
+                table.print_csv();
+            } else {
+                table.print();
+            }
+
+            Ok(())
+        })?;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("generate-weights") {
+        let config = parse_weight_config(matches)?;
+        let template = match matches.value_of("template") {
+            Some(path) => std::fs::read_to_string(path)?,
+            None => DEFAULT_TEMPLATE.to_string(),
+        };
+
+        // Generated code is not highlighted
+        render_path(matches, |collection, _| {
+            apply_overhead(collection, matches.value_of("overhead"))?;
+
+            let mut table = collection.generate_weight_table(&config)?;
+            table.sort_by_extrinsic();
+
+            let module = codegen::render_weights(&table, &template)?;
+            match matches.value_of("output") {
+                Some(path) => std::fs::write(path, module)?,
+                None => print!("{}", module),
+            }
+
+            Ok(())
+        })?;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("compare-weights") {
+        let config = parse_weight_config(matches)?;
+        let tolerance = if matches.is_present("tolerance") {
+            value_t!(matches, "tolerance", f64)?
+        } else {
+            10.0
+        };
+
+        render_path(matches, |collection, highlighter| {
+            apply_overhead(collection, matches.value_of("overhead"))?;
+
+            // Unwrapping is ok, since "WEIGHTS_FILE" is set to required. Read on
+            // every rendering, since it's watched as well.
+            let functions =
+                weightfile::read_weight_file(matches.value_of("WEIGHTS_FILE").unwrap())?;
+
+            let mut table = collection.compare_weights(
+                &functions,
+                matches.value_of("pallet"),
+                &config,
+                tolerance,
+            )?;
+            if !matches.is_present("all") {
+                table.retain_deviations();
+            }
+            table.sort_by_percentage();
+
+            if let Some(highlighter) = highlighter {
+                table.print_highlighted(highlighter);
+            } else if matches.is_present("csv") {
+                table.print_csv();
+            } else {
+                table.print();
+            }
+
+            Ok(())
+        })?;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("check-components") {
+        render_path(matches, |collection, highlighter| {
+            // Unwrapping is ok, since "WEIGHTS_FILE" is set to required. Read on
+            // every rendering, since it's watched as well.
+            let functions =
+                weightfile::read_weight_file(matches.value_of("WEIGHTS_FILE").unwrap())?;
+
+            let mut table = collection.check_components(&functions, matches.value_of("pallet"))?;
+            table.sort_by_extrinsic();
+
+            if matches.is_present("csv") {
+                table.print_csv();
+            } else if table.is_empty() {
+                println!("All components match the weight functions.");
+            } else if let Some(highlighter) = highlighter {
+                table.print_highlighted(highlighter);
+            } else {
+                table.print();
+            }
+
+            Ok(())
+        })?;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("capacity") {
+        let config = parse_capacity_config(matches)?;
+
+        render_path(matches, |collection, highlighter| {
+            apply_overhead(collection, matches.value_of("overhead"))?;
+
+            let mut table = collection
+                .generate_capacity_table(&config, parse_metric(matches.value_of("metric")))?;
+            table.sort_by_worst_case();
+
+            if let Some(highlighter) = highlighter {
+                table.print_highlighted(highlighter);
+            } else if matches.is_present("csv") {
+                table.print_csv();
+            } else {
+                table.print();
+            }
+
+            Ok(())
+        })?;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("check-limits") {
+        // Unwrapping is ok, since "config" is set to required
+        let config = LimitsConfig::read(matches.value_of("config").unwrap())?;
+
+        render_path(matches, |collection, highlighter| {
+            apply_overhead(collection, matches.value_of("overhead"))?;
+
+            let mut table = collection.check_limits(&config)?;
+            table.sort_by_excess();
+
+            if let Some(path) = matches.value_of("junit") {
+                std::fs::write(path, table.to_junit())?;
+            }
+
+            let offenders = table.offender_count();
+            if offenders == 0 {
+                println!("All extrinsics are within their ceiling.");
+                return Ok(());
+            }
+
+            if let Some(highlighter) = highlighter {
+                table.print_highlighted(highlighter);
+            } else if matches.is_present("csv") {
+                table.print_csv();
+            } else {
+                table.print();
+            }
+
+            Err(failure::err_msg(format!(
+                "{} extrinsic(s) exceed their ceiling",
+                offenders
+            )))
+        })?;
+    }
+
+    if let Some(matches) = matches.subcommand_matches("compare") {
+        let metric = parse_metric(matches.value_of("metric"));
+        let normalization = parse_normalization(matches)?;
+        let alpha = if matches.is_present("alpha") {
+            value_t!(matches, "alpha", f64)?
+        } else {
+            0.05
+        };
+
+        let compare = |old: &mut ExtrinsicCollection,
+                       new: &mut ExtrinsicCollection,
+                       highlighter: Option<&mut RowHighlighter>|
+         -> Result<(), Error> {
+            apply_overhead(old, matches.value_of("overhead"))?;
+            apply_overhead(new, matches.value_of("overhead"))?;
+
+            if let Some(normalization) = &normalization {
+                let factor = normalization.apply(old, new, metric)?;
+                // Printed to stderr, so that CSV and Markdown output stay intact
+                eprintln!(
+                    "Adjusted for hardware, the new machine takes {}x as long as the old one.",
+                    factor
+                );
+            }
+
+            if matches.is_present("per-step") {
+                let mut table = old.compare_steps(new, metric)?;
+                table.sort_by_extrinsic();
+                if matches.is_present("significant-only") {
+                    table.retain_significant(alpha);
+                }
+
+                if let Some(highlighter) = highlighter {
+                    table.print_highlighted(highlighter);
+                } else if matches.is_present("csv") {
+                    table.print_csv();
+                } else if matches.is_present("markdown") {
+                    table.print_markdown();
+                } else {
+                    table.print();
+                }
+
+                return Ok(());
+            }
+
+            let mut table = old.compare(new, metric)?;
+            table.sort_by_change();
+            if matches.is_present("significant-only") {
+                table.retain_significant(alpha);
+            }
+
+            if let Some(highlighter) = highlighter {
+                table.print_highlighted(highlighter);
+            } else if matches.is_present("csv") {
+                table.print_csv();
+            } else if matches.is_present("markdown") {
+                table.print_markdown();
+            } else {
+                table.print();
+            }
+
+            Ok(())
+        };
+
+        // Unwrapping is ok, since "OLD_PATH" and "NEW_PATH" are set to required
+        let old_path = matches.value_of("OLD_PATH").unwrap();
+        let new_path = matches.value_of("NEW_PATH").unwrap();
+        let skip_warn = matches.is_present("skip-warnings");
+
+        if matches.is_present("watch") {
+            watch(
+                &[old_path, new_path],
+                &[],
+                skip_warn,
+                |collections, highlighter| {
+                    let (old, new) = collections.split_at_mut(1);
+                    compare(&mut old[0], &mut new[0], highlighter)
+                },
+            )?;
+        } else {
+            let (mut old, old_commit) = read_collection(old_path, skip_warn)?;
+            let (mut new, new_commit) = read_collection(new_path, skip_warn)?;
+            print_commits(matches, old_commit.as_deref(), new_commit.as_deref());
+            compare(&mut old, &mut new, None)?;
+        }
+    }
+
+    if let Some(matches) = matches.subcommand_matches("record") {
         // Unwrapping is ok, since "PATH" is set to required
-        let collection = build_collection(
+        let (collection, source_commit) = read_collection(
             matches.value_of("PATH").unwrap(),
             matches.is_present("skip-warnings"),
         )?;
 
-        let mut table = collection.generate_ratio_table()?;
-        table.sort_by_ratio();
+        // Unwrapping is ok, since "label" is set to required
+        let mut metadata = RunMetadata::new(matches.value_of("label").unwrap());
+        metadata.commit = matches
+            .value_of("commit")
+            .map(|commit| commit.to_string())
+            .or(source_commit);
+        metadata.machine = matches.value_of("machine").map(|s| s.to_string());
+        metadata.runtime = matches.value_of("runtime").map(|s| s.to_string());
+        if matches.is_present("speed-factor") {
+            metadata.speed_factor = Some(value_t!(matches, "speed-factor", f64)?);
+        }
+
+        let store = HistoryStore::open(matches.value_of("history").unwrap_or(DEFAULT_HISTORY_DIR))?;
+        let path = store.record(&metadata, &collection)?;
+        println!(
+            "Recorded {} extrinsics as \"{}\" in {}",
+            collection.len(),
+            metadata.label,
+            path.display()
+        );
+    }
+
+    if let Some(matches) = matches.subcommand_matches("history") {
+        // Unwrapping is ok, since "EXTRINSIC" is set to required
+        let id: ExtrinsicId = matches.value_of("EXTRINSIC").unwrap().parse()?;
+
+        let store = HistoryStore::open(matches.value_of("history").unwrap_or(DEFAULT_HISTORY_DIR))?;
+        let mut runs = store.runs()?;
+        for run in &mut runs {
+            apply_overhead(&mut run.collection, matches.value_of("overhead"))?;
+        }
+        history::normalize(
+            &mut runs,
+            &parse_ids(matches, "reference")?,
+            parse_metric(matches.value_of("metric")),
+        )?;
+
+        let table = history::trend(&runs, &id, parse_metric(matches.value_of("metric")))?;
 
         if matches.is_present("csv") {
             table.print_csv();
+        } else if matches.is_present("json") {
+            println!("{}", table.to_json());
         } else {
             table.print();
         }
     }
 
-    if let Some(matches) = matches.subcommand_matches("per-step") {
+    if let Some(matches) = matches.subcommand_matches("detect-changes") {
+        let mut config = DetectionConfig::default();
+        if matches.is_present("threshold") {
+            config.threshold = value_t!(matches, "threshold", f64)?;
+        }
+        if matches.is_present("min-change") {
+            config.min_change = value_t!(matches, "min-change", f64)?;
+        }
+
+        let store = HistoryStore::open(matches.value_of("history").unwrap_or(DEFAULT_HISTORY_DIR))?;
+        let mut runs = store.runs()?;
+        for run in &mut runs {
+            apply_overhead(&mut run.collection, matches.value_of("overhead"))?;
+        }
+        history::normalize(
+            &mut runs,
+            &parse_ids(matches, "reference")?,
+            parse_metric(matches.value_of("metric")),
+        )?;
+
+        let table =
+            history::detect_changes(&runs, parse_metric(matches.value_of("metric")), &config)?;
+
+        if matches.is_present("csv") {
+            table.print_csv();
+        } else if table.is_empty() {
+            println!("No changes detected.");
+        } else {
+            table.print();
+        }
+    }
+
+    if let Some(matches) = matches.subcommand_matches("notify") {
+        // Unwrapping is ok, since "OLD_PATH" and "NEW_PATH" are set to required
+        let (old_path, new_path) = (
+            matches.value_of("OLD_PATH").unwrap(),
+            matches.value_of("NEW_PATH").unwrap(),
+        );
+        let (mut old, old_commit) = read_collection(old_path, matches.is_present("skip-warnings"))?;
+        let (mut new, new_commit) = read_collection(new_path, matches.is_present("skip-warnings"))?;
+        apply_overhead(&mut old, matches.value_of("overhead"))?;
+        apply_overhead(&mut new, matches.value_of("overhead"))?;
+
+        // Unwrapping is ok, since "config" is set to required
+        let config = NotifyConfig::read(matches.value_of("config").unwrap())?;
+
+        let metric = parse_metric(matches.value_of("metric"));
+        let hardware_factor = parse_normalization(matches)?
+            .map(|normalization| normalization.apply(&mut old, &mut new, metric))
+            .transpose()?;
+
+        let mut table = old.compare(&new, metric)?;
+        table.sort_by_change();
+
+        let payload = Payload {
+            old: old_path.to_string(),
+            new: new_path.to_string(),
+            old_commit,
+            new_commit,
+            hardware_factor,
+            regressions: config.evaluate(&table),
+        };
+
+        if matches.is_present("dry-run") {
+            println!("{}", serde_json::to_string_pretty(&payload)?);
+            return Ok(());
+        }
+
+        let delivered = config.flush_outbox()?;
+        if delivered > 0 {
+            println!("Delivered {} payload(s) of the outbox.", delivered);
+        }
+
+        if payload.regressions.is_empty() {
+            println!("No regressions found.");
+            return Ok(());
+        }
+
+        config.deliver(&payload)?;
+        println!("Reported {} regression(s).", payload.regressions.len());
+    }
+
+    if let Some(matches) = matches.subcommand_matches("baseline") {
+        if let Some(matches) = matches.subcommand_matches("accept") {
+            // Unwrapping is ok, since "PATH" is set to required
+            let mut collection = build_collection(
+                matches.value_of("PATH").unwrap(),
+                matches.is_present("skip-warnings"),
+            )?;
+            apply_overhead(&mut collection, matches.value_of("overhead"))?;
+
+            let snapshot = BaselineSnapshot::new(
+                &collection,
+                parse_metric(matches.value_of("metric")),
+                matches.value_of("overhead"),
+            )?;
+            let path = matches.value_of("file").unwrap_or(DEFAULT_BASELINE_FILE);
+            snapshot.write(path)?;
+            println!(
+                "Accepted {} extrinsics as the baseline in {}",
+                collection.len(),
+                path
+            );
+        }
+
+        if let Some(matches) = matches.subcommand_matches("check") {
+            let snapshot =
+                BaselineSnapshot::read(matches.value_of("file").unwrap_or(DEFAULT_BASELINE_FILE))?;
+
+            // Unwrapping is ok, since "PATH" is set to required
+            let mut collection = build_collection(
+                matches.value_of("PATH").unwrap(),
+                matches.is_present("skip-warnings"),
+            )?;
+            // The same overhead as the one of the baseline
+            apply_overhead(&mut collection, snapshot.overhead.as_deref())?;
+
+            let mut config = match matches.value_of("config") {
+                Some(path) => BaselineConfig::read(path)?,
+                None => BaselineConfig::default(),
+            };
+            if matches.is_present("tolerance") {
+                config.tolerance = Some(value_t!(matches, "tolerance", f64)?);
+            }
+
+            let mut table = snapshot.check(&collection, &config, &baseline::today());
+            table.sort_by_extrinsic();
+
+            if table.is_empty() {
+                println!("The run matches the baseline.");
+                return Ok(());
+            }
+
+            if matches.is_present("csv") {
+                table.print_csv();
+            } else {
+                table.print();
+            }
+
+            let unexplained = table.unexplained_count();
+            if unexplained > 0 {
+                return Err(failure::err_msg(format!(
+                    "{} deviation(s) from the baseline are not explained",
+                    unexplained
+                )));
+            }
+        }
+    }
+
+    if let Some(matches) = matches.subcommand_matches("sign-off") {
+        // Unwrapping is ok, since "PATH" is set to required
+        let mut collection = build_collection(
+            matches.value_of("PATH").unwrap(),
+            matches.is_present("skip-warnings"),
+        )?;
+        apply_overhead(&mut collection, matches.value_of("overhead"))?;
+
+        let ids = parse_ids(matches, "extrinsic")?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0);
+
+        let path = matches.value_of("ledger").unwrap_or(DEFAULT_LEDGER_FILE);
+        let mut ledger = ReviewLedger::open(path)?;
+        // Unwrapping is ok, since "reviewer" is set to required
+        let reviewer = matches.value_of("reviewer").unwrap();
+        let approved = ledger.sign_off(&collection, reviewer, &ids, timestamp)?;
+        ledger.write(path)?;
+
+        println!(
+            "Recorded the approval of {} extrinsic(s) by {} in {}",
+            approved, reviewer, path
+        );
+    }
+
+    if let Some(matches) = matches.subcommand_matches("reviews") {
         // Unwrapping is ok, since "PATH" is set to required
-        let collection = build_collection(
+        let mut collection = build_collection(
             matches.value_of("PATH").unwrap(),
             matches.is_present("skip-warnings"),
         )?;
+        apply_overhead(&mut collection, matches.value_of("overhead"))?;
 
-        let mut table = collection.generate_step_table()?;
-        table.sort_by_extrinsic_incr_percentage();
+        let ledger = ReviewLedger::open(matches.value_of("ledger").unwrap_or(DEFAULT_LEDGER_FILE))?;
+        let mut table = ledger.review(&collection)?;
+        table.sort_by_extrinsic();
 
         if matches.is_present("csv") {
             table.print_csv();
@@ -78,5 +1366,76 @@ fn main() -> Result<(), Error> {
         }
     }
 
+    if let Some(matches) = matches.subcommand_matches("rank-stability") {
+        let metric = parse_metric(matches.value_of("metric"));
+        let top = if matches.is_present("top") {
+            value_t!(matches, "top", usize)?
+        } else {
+            10
+        };
+
+        let rank = |old: &mut ExtrinsicCollection,
+                    new: &mut ExtrinsicCollection,
+                    highlighter: Option<&mut RowHighlighter>|
+         -> Result<(), Error> {
+            apply_overhead(old, matches.value_of("overhead"))?;
+            apply_overhead(new, matches.value_of("overhead"))?;
+
+            let mut table = old.compare_ranks(new, metric)?;
+            table.sort_by_movement();
+            let ranked = table.len();
+            table.retain_moved(top);
+
+            if matches.is_present("csv") {
+                table.print_csv();
+                return Ok(());
+            }
+
+            let display = |value: Option<f64>| {
+                value
+                    .map(|value| value.to_string())
+                    .unwrap_or_else(|| String::from("-"))
+            };
+            println!(
+                "Kendall's tau: {}, Spearman's rho: {} ({} extrinsics in both runs)\n",
+                display(table.kendall_tau()),
+                display(table.spearman()),
+                ranked
+            );
+
+            if table.is_empty() {
+                println!("The ordering did not change.");
+            } else if let Some(highlighter) = highlighter {
+                table.print_highlighted(highlighter);
+            } else {
+                table.print();
+            }
+
+            Ok(())
+        };
+
+        // Unwrapping is ok, since "OLD_PATH" and "NEW_PATH" are set to required
+        let old_path = matches.value_of("OLD_PATH").unwrap();
+        let new_path = matches.value_of("NEW_PATH").unwrap();
+        let skip_warn = matches.is_present("skip-warnings");
+
+        if matches.is_present("watch") {
+            watch(
+                &[old_path, new_path],
+                &[],
+                skip_warn,
+                |collections, highlighter| {
+                    let (old, new) = collections.split_at_mut(1);
+                    rank(&mut old[0], &mut new[0], highlighter)
+                },
+            )?;
+        } else {
+            let (mut old, old_commit) = read_collection(old_path, skip_warn)?;
+            let (mut new, new_commit) = read_collection(new_path, skip_warn)?;
+            print_commits(matches, old_commit.as_deref(), new_commit.as_deref());
+            rank(&mut old, &mut new, None)?;
+        }
+    }
+
     Ok(())
 }
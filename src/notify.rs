@@ -0,0 +1,377 @@
+//! Notifications about regressions between two runs, delivered to a webhook.
+
+use crate::tables::ComparisonTable;
+use failure::Error;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Outbox of the undelivered payloads if none is specified.
+pub const DEFAULT_OUTBOX: &str = ".bench-outbox.jsonl";
+
+/// Timeout of connecting to, writing to and reading from the webhook.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[allow(non_local_definitions)]
+mod error {
+    #[derive(Debug, Fail)]
+    pub(super) enum NotifyError {
+        #[fail(
+            display = "unsupported webhook URL \"{}\", expected http(s)://<host>[:<port>]/<path>",
+            0
+        )]
+        UnsupportedUrl(String),
+        #[fail(
+            display = "invalid retry_delay {}, expected a non-negative amount of seconds",
+            0
+        )]
+        InvalidRetryDelay(f64),
+        #[fail(display = "webhook responded with \"{}\"", 0)]
+        UnexpectedResponse(String),
+        #[fail(
+            display = "delivery failed after {} attempt(s), the payload was written to {}: {}",
+            attempts, outbox, cause
+        )]
+        DeliveryFailed {
+            attempts: u32,
+            outbox: String,
+            cause: String,
+        },
+    }
+}
+
+use error::NotifyError::*;
+
+/// Webhook and regression rules as configured in a TOML file, e.g.
+///
+/// ```toml
+/// url = "https://hooks.example.com/benchmarks"
+/// # Attempts after the first one failed (default: 3)
+/// retries = 3
+/// # Delay before the first retry in seconds, doubled on every retry (default: 1)
+/// retry_delay = 1.0
+///
+/// [[rules]]
+/// name = "significant regression"
+/// max_increase = 5.0
+/// significant_only = true
+///
+/// [[rules]]
+/// name = "balances regression"
+/// pallet = "balances"
+/// max_increase = 1.0
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NotifyConfig {
+    pub url: String,
+    pub retries: Option<u32>,
+    pub retry_delay: Option<f64>,
+    /// File the undelivered payloads are appended to (default: `.bench-outbox.jsonl`).
+    pub outbox: Option<PathBuf>,
+    #[serde(default)]
+    pub rules: Vec<RegressionRule>,
+}
+
+/// Reports the extrinsics whose average time increased by more than
+/// `max_increase` percent.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RegressionRule {
+    pub name: String,
+    pub max_increase: f64,
+    /// Limits the rule to a pallet.
+    pub pallet: Option<String>,
+    /// Limits the rule to an extrinsic, as `pallet.extrinsic`.
+    pub extrinsic: Option<String>,
+    /// Only reports the increases which are statistically significant.
+    #[serde(default)]
+    pub significant_only: bool,
+    /// Significance level, used with `significant_only` (default: 0.05).
+    pub alpha: Option<f64>,
+}
+
+impl RegressionRule {
+    fn matches(&self, pallet: &str, extrinsic: &str) -> bool {
+        self.pallet.as_ref().is_none_or(|p| p == pallet)
+            && self
+                .extrinsic
+                .as_ref()
+                .is_none_or(|e| *e == format!("{}.{}", pallet, extrinsic))
+    }
+}
+
+/// An extrinsic which violates a rule.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Regression {
+    pub rule: String,
+    pub pallet: String,
+    pub extrinsic: String,
+    pub old_time: f64,
+    pub new_time: f64,
+    pub percentage: f64,
+    pub t_test_p: Option<f64>,
+    pub mann_whitney_p: Option<f64>,
+}
+
+/// JSON body which is posted to the webhook.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Payload {
+    /// Location of the old run.
+    pub old: String,
+    /// Location of the new run.
+    pub new: String,
+    /// Commit of the old run, if read from git.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_commit: Option<String>,
+    /// Commit of the new run, if read from git.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_commit: Option<String>,
+    /// How much longer the new machine takes than the old one, if the times were
+    /// adjusted for hardware.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hardware_factor: Option<f64>,
+    pub regressions: Vec<Regression>,
+}
+
+impl NotifyConfig {
+    /// Reads and parses the config file at the given path.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+    fn outbox(&self) -> &Path {
+        self.outbox
+            .as_deref()
+            .unwrap_or_else(|| Path::new(DEFAULT_OUTBOX))
+    }
+    /// Evaluates the rules against the comparison. An extrinsic is reported once
+    /// for every rule it violates, added and removed extrinsics are ignored.
+    pub fn evaluate(&self, table: &ComparisonTable) -> Vec<Regression> {
+        let mut regressions = vec![];
+
+        for rule in &self.rules {
+            for ((pallet, extrinsic, old_time, new_time, _, percentage), significance) in
+                table.raw_list().into_iter().zip(table.raw_significance())
+            {
+                let (old_time, new_time, percentage) = match (old_time, new_time, percentage) {
+                    (Some(old_time), Some(new_time), Some(percentage)) => {
+                        (old_time, new_time, percentage)
+                    }
+                    _ => continue,
+                };
+
+                if !rule.matches(pallet, extrinsic)
+                    || percentage <= rule.max_increase
+                    || (rule.significant_only
+                        && !significance.is_significant(rule.alpha.unwrap_or(0.05)))
+                {
+                    continue;
+                }
+
+                regressions.push(Regression {
+                    rule: rule.name.clone(),
+                    pallet: pallet.to_string(),
+                    extrinsic: extrinsic.to_string(),
+                    old_time,
+                    new_time,
+                    percentage,
+                    t_test_p: significance.t_test_p,
+                    mann_whitney_p: significance.mann_whitney_p,
+                });
+            }
+        }
+
+        regressions
+    }
+    /// Posts the payload to the webhook, retrying with an exponential backoff. If
+    /// all attempts fail, the payload is appended to the outbox.
+    pub fn deliver(&self, payload: &Payload) -> Result<(), Error> {
+        let body = serde_json::to_string(payload)?;
+
+        if let Err(err) = self.post_with_retries(&body) {
+            append_line(self.outbox(), &body)?;
+            return Err(DeliveryFailed {
+                attempts: self.retries.unwrap_or(3) + 1,
+                outbox: self.outbox().display().to_string(),
+                cause: err.to_string(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+    /// Delivers the payloads of the outbox. Payloads which still can not be
+    /// delivered stay in the outbox. Returns the amount of delivered payloads, zero
+    /// if there is no outbox.
+    pub fn flush_outbox(&self) -> Result<usize, Error> {
+        let content = match fs::read_to_string(self.outbox()) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(0),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut delivered = 0;
+        let mut pending = String::new();
+        for body in content.lines().filter(|line| !line.trim().is_empty()) {
+            if post(&self.url, body).is_ok() {
+                delivered += 1;
+            } else {
+                pending.push_str(body);
+                pending.push('\n');
+            }
+        }
+
+        if pending.is_empty() {
+            fs::remove_file(self.outbox())?;
+        } else {
+            fs::write(self.outbox(), pending)?;
+        }
+
+        Ok(delivered)
+    }
+    fn post_with_retries(&self, body: &str) -> Result<(), Error> {
+        let mut delay = self.retry_delay.unwrap_or(1.0);
+        let mut attempt = 0;
+
+        loop {
+            match post(&self.url, body) {
+                Ok(()) => return Ok(()),
+                Err(err) if attempt >= self.retries.unwrap_or(3) => return Err(err),
+                Err(_) => {
+                    thread::sleep(Duration::from_secs_f64(delay));
+                    delay *= 2.0;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for NotifyConfig {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let config: NotifyConfig = toml::from_str(s)?;
+        // Reject unsupported URLs and delays before any delivery is attempted
+        check_url(&config.url)?;
+        if let Some(delay) = config.retry_delay.filter(|d| !d.is_finite() || *d < 0.0) {
+            return Err(InvalidRetryDelay(delay).into());
+        }
+        Ok(config)
+    }
+}
+
+/// Checks that the URL is an `http://` or `https://` URL with a host and an
+/// optional numeric port.
+fn check_url(url: &str) -> Result<(), Error> {
+    let unsupported = || UnsupportedUrl(url.to_string());
+
+    let rest = url
+        .strip_prefix("http://")
+        .or_else(|| url.strip_prefix("https://"))
+        .ok_or_else(unsupported)?;
+    let authority = rest.split('/').next().unwrap_or_default();
+
+    let host = match authority.rfind(':') {
+        Some(index) => {
+            authority[index + 1..]
+                .parse::<u16>()
+                .map_err(|_| unsupported())?;
+            &authority[..index]
+        }
+        None => authority,
+    };
+
+    if host.is_empty() {
+        return Err(unsupported().into());
+    }
+
+    Ok(())
+}
+
+/// Posts the JSON body to the URL, succeeds on a `2xx` status.
+fn post(url: &str, body: &str) -> Result<(), Error> {
+    let agent = ureq::AgentBuilder::new().timeout(TIMEOUT).build();
+    let response = match agent
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(body)
+    {
+        Ok(response) => response,
+        Err(ureq::Error::Status(_, response)) => response,
+        Err(err) => return Err(err.into()),
+    };
+
+    // e.g. "200 OK"
+    match response.status() {
+        200..=299 => Ok(()),
+        status => Err(UnexpectedResponse(format!("{} {}", status, response.status_text())).into()),
+    }
+}
+
+fn append_line(path: &Path, line: &str) -> Result<(), Error> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_url() {
+        assert!(check_url("http://127.0.0.1:8080/hooks/bench").is_ok());
+        assert!(check_url("http://example.com").is_ok());
+        assert!(check_url("https://example.com/hook").is_ok());
+        assert!(check_url("ftp://example.com/hook").is_err());
+        assert!(check_url("http://example.com:http/hook").is_err());
+        assert!(check_url("http:///hook").is_err());
+    }
+
+    #[test]
+    fn test_rule_matches() {
+        let config: NotifyConfig = r#"
+            url = "http://127.0.0.1:8080/"
+
+            [[rules]]
+            name = "any"
+            max_increase = 5.0
+
+            [[rules]]
+            name = "transfer"
+            extrinsic = "balances.transfer"
+            pallet = "balances"
+            max_increase = 1.0
+        "#
+        .parse()
+        .unwrap();
+
+        assert!(config.rules[0].matches("staking", "bond"));
+        assert!(config.rules[1].matches("balances", "transfer"));
+        assert!(!config.rules[1].matches("balances", "set_balance"));
+        assert!(!config.rules[1].matches("staking", "transfer"));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!("url = \"ftp://example.com/\""
+            .parse::<NotifyConfig>()
+            .is_err());
+        assert!("url = \"http://example.com/\"\nretry = 3"
+            .parse::<NotifyConfig>()
+            .is_err());
+        assert!("url = \"http://example.com/\"\nretry_delay = -1.0"
+            .parse::<NotifyConfig>()
+            .is_err());
+        assert!("url = \"http://example.com/\"\nretry_delay = nan"
+            .parse::<NotifyConfig>()
+            .is_err());
+        assert!("url = \"http://example.com/\"\nretry_delay = 0.0"
+            .parse::<NotifyConfig>()
+            .is_ok());
+    }
+}
@@ -2,17 +2,47 @@ use super::{filescraper::FileContent, ExtrinsicResult, StepRepeatEntry};
 
 use failure::Error;
 
-#[derive(Debug, Fail)]
-enum AnalyserError {
-    #[fail(display = "header value of the benchmark result is missing")]
-    MissingHeader,
-    #[fail(display = "header value of the benchmark result is invalid")]
-    InvalidHeader,
-    #[fail(display = "body of the benchmark result is invalid")]
-    InvalidBody,
+#[allow(non_local_definitions)]
+mod error {
+    #[derive(Debug, Fail)]
+    pub(super) enum AnalyserError {
+        #[fail(display = "header value of the benchmark result is missing")]
+        MissingHeader,
+        #[fail(display = "header value of the benchmark result is invalid")]
+        InvalidHeader,
+        #[fail(display = "body of the benchmark result is invalid")]
+        InvalidBody,
+    }
+}
+
+use self::error::AnalyserError::*;
+
+/// Describes the columns of the result body, as specified by the second header line.
+#[derive(Debug, PartialEq)]
+pub(crate) struct BodyLayout {
+    pub column_count: usize,
+    pub var_count: usize,
+    pub extrinsic_time: usize,
+    pub storage_root_time: usize,
+    /// Optional, only available in newer result files.
+    pub reads: Option<usize>,
+    pub writes: Option<usize>,
 }
 
-use self::AnalyserError::*;
+impl BodyLayout {
+    /// Layout of the original format: variables + "extrinsic_time" + "storage_root_time".
+    #[cfg(test)]
+    fn with_vars(var_count: usize) -> Self {
+        BodyLayout {
+            column_count: var_count + 2,
+            var_count,
+            extrinsic_time: var_count,
+            storage_root_time: var_count + 1,
+            reads: None,
+            writes: None,
+        }
+    }
+}
 
 /// Parses the header of the result file. This function has slightly stricter requirements.
 ///
@@ -21,19 +51,21 @@ use self::AnalyserError::*;
 /// Pallet: "balances", Extrinsic: "set_balance", Lowest values: [], Highest values: [], Steps: [10], Repeat: 10
 /// u,e,extrinsic_time,storage_root_time
 /// ```
+///
+/// Newer result files append the database operations to the second line, e.g.
+/// `u,e,extrinsic_time,storage_root_time,reads,repeat_reads,writes,repeat_writes`.
 #[rustfmt::skip]
-pub(crate) fn parse_header(content: &FileContent) -> Result<ExtrinsicResult, Error> {
+pub(crate) fn parse_header(content: &FileContent) -> Result<(ExtrinsicResult, BodyLayout), Error> {
     let mut extrinsic_result = ExtrinsicResult::default();
 
     let lines: Vec<&str> = (content.0).0.lines().take(2).collect();
 
     // Parse the first line
     {
-        let parts: Vec<&str> = lines
-            .get(0)
-            .ok_or(MissingHeader)?
-            .split_whitespace()
-            .collect();
+        // Lists like `[1000, 50]` are collapsed into `[1000,50]`, so they
+        // stay a single part.
+        let line = collapse_lists(lines.first().ok_or(MissingHeader)?);
+        let parts: Vec<&str> = line.split_whitespace().collect();
 
         // Length is checked here, so directly indexing
         // the vector after this is safe.
@@ -47,11 +79,15 @@ pub(crate) fn parse_header(content: &FileContent) -> Result<ExtrinsicResult, Err
         extrinsic_result.extrinsic =
             check_requirements(parts[2], parts[3], "Extrinsic:", "\"", "\",")?;
 
-        // Parse steps amount
-        extrinsic_result.steps =
-            check_requirements(parts[10], parts[11], "Steps:", "[", "],")?
-                .parse::<usize>()
-                .map_err(|_| InvalidHeader)?;
+        // Parse the highest component values, empty if not specified
+        check(|| parts[7] == "Highest")?;
+        extrinsic_result.highest_values = parse_list(parts[8], parts[9], "values:")?;
+
+        // Parse steps amount. Newer result files specify the steps per
+        // component, e.g. `[10,10]`, in which case the first one is used.
+        extrinsic_result.steps = *parse_list(parts[10], parts[11], "Steps:")?
+            .first()
+            .ok_or(InvalidHeader)? as usize;
 
         // Parse repeat amount. The amount does not have brackets around it,
         // probably skipped by accident. Generally not an issue, just a
@@ -92,42 +128,55 @@ pub(crate) fn parse_header(content: &FileContent) -> Result<ExtrinsicResult, Err
             .for_each(|var| {
                 extrinsic_result.input_var_names.push(var.to_string())
             });
-    }
 
-    Ok(extrinsic_result)
+        // Checked above, so unwrapping is safe.
+        let position = |name: &str| parts.iter().position(|part| part == &name);
+        let layout = BodyLayout {
+            column_count: parts.len(),
+            var_count: offset,
+            extrinsic_time: position("extrinsic_time").unwrap(),
+            storage_root_time: position("storage_root_time").unwrap(),
+            reads: position("reads"),
+            writes: position("writes"),
+        };
+
+        Ok((extrinsic_result, layout))
+    }
 }
 
 pub(crate) fn parse_body(
     content: &FileContent,
-    expected_len: usize,
+    layout: &BodyLayout,
 ) -> Result<Vec<StepRepeatEntry>, Error> {
     let mut coll = Vec::new();
     let lines: Vec<&str> = (content.0).0.lines().skip(2).collect();
 
     for line in lines {
-        let parts: Vec<&str> = line.split(",").collect();
+        let parts: Vec<&str> = line.split(',').collect();
 
         // Must have the expected length:
-        // -> variables + "extrinsic_time" + "storage_root_time"
-        if parts.len() != expected_len || parts.len() < 2 {
+        // -> variables + "extrinsic_time" + "storage_root_time" (+ database operations)
+        if parts.len() != layout.column_count || parts.len() < 2 {
             break;
         }
 
         // All parts must be numeric
-        if parts.iter().all(|p| !p.parse::<usize>().is_ok()) {
+        if parts.iter().all(|p| p.parse::<usize>().is_err()) {
             break;
         }
 
         let mut repeat_entry = StepRepeatEntry::default();
 
-        // Fill in the data. The length and conversion validity is checked above,
-        // so directly indexing and unwrapping is safe here.
-        let temp: Vec<&&str> = parts.iter().rev().take(2).collect();
-        repeat_entry.storage_root_time = temp[0].parse::<u64>().map_err(|_| InvalidBody)?;
-        repeat_entry.extrinsic_time = temp[1].parse::<u64>().map_err(|_| InvalidBody)?;
+        // Fill in the data. The length is checked above, so directly indexing is
+        // safe here.
+        let value = |index: usize| parts[index].parse::<u64>().map_err(|_| InvalidBody);
+        repeat_entry.extrinsic_time = value(layout.extrinsic_time)?;
+        repeat_entry.storage_root_time = value(layout.storage_root_time)?;
+        repeat_entry.reads = layout.reads.map(value).transpose()?.unwrap_or(0);
+        repeat_entry.writes = layout.writes.map(value).transpose()?.unwrap_or(0);
         repeat_entry.input_vars = parts
             .iter()
-            .take(expected_len - 2)
+            .take(layout.var_count)
             .map(|p| p.parse::<u64>())
             .collect::<Result<Vec<u64>, _>>()?;
 
@@ -164,6 +213,36 @@ fn check_requirements(
         .replace(",", "")) // Remove any tangling comma
 }
 
+/// Parses a list value of the header, e.g. `[1000,50],` (see `collapse_lists`).
+fn parse_list(input_key: &str, input_val: &str, key_name: &str) -> Result<Vec<u64>, Error> {
+    check(|| input_key == key_name)?;
+    check(|| input_val.starts_with('[') && input_val.ends_with("],"))?;
+
+    input_val
+        .trim_start_matches('[')
+        .trim_end_matches("],")
+        .split(',')
+        .filter(|value| !value.is_empty())
+        .map(|value| value.parse::<u64>().map_err(|_| InvalidHeader.into()))
+        .collect()
+}
+
+/// Removes the whitespace within square brackets, e.g.
+/// `Highest values: [1000, 50],` -> `Highest values: [1000,50],`.
+fn collapse_lists(line: &str) -> String {
+    let mut in_list = false;
+    line.chars()
+        .filter(|c| {
+            match c {
+                '[' => in_list = true,
+                ']' => in_list = false,
+                _ => {}
+            }
+            !(in_list && c.is_whitespace())
+        })
+        .collect()
+}
+
 fn check<F>(func: F) -> Result<(), Error>
 where
     F: Fn() -> bool,
@@ -218,20 +297,77 @@ mod tests {
 
         for ((str1, str2), output) in &test_data {
             let content = FileContent((format!("{}\n{}", str1, str2), PathBuf::from("")));
-            let res = parse_header(&content).unwrap();
+            let (res, layout) = parse_header(&content).unwrap();
+            assert_eq!(layout, BodyLayout::with_vars(output.4.len()));
             assert_eq!(res.pallet, output.0);
             assert_eq!(res.extrinsic, output.1);
             assert_eq!(res.steps, output.2);
             assert_eq!(res.repeats, output.3);
 
-            let mut counter = 0;
-            for var in &output.4 {
+            for (counter, var) in output.4.iter().enumerate() {
                 assert_eq!(&res.input_var_names[counter], var);
-                counter += 1;
             }
         }
     }
 
+    #[test]
+    fn test_parse_header_values() {
+        let content = FileContent((
+            String::from(
+                "Pallet: \"balances\", Extrinsic: \"transfer\", Lowest values: [1, 2], Highest values: [1000, 5000], Steps: [20, 10], Repeat: 5\n\
+                u,e,extrinsic_time,storage_root_time",
+            ),
+            PathBuf::from(""),
+        ));
+
+        let (res, _) = parse_header(&content).unwrap();
+        assert_eq!(res.highest_values, vec![1000, 5000]);
+        assert_eq!(res.steps, 20);
+        assert_eq!(res.repeats, 5);
+
+        assert_eq!(collapse_lists("a: [1, 2], b: []"), "a: [1,2], b: []");
+        assert_eq!(
+            parse_list("values:", "[],", "values:").unwrap(),
+            Vec::<u64>::new()
+        );
+        assert!(parse_list("values:", "[1,x],", "values:").is_err());
+    }
+
+    #[test]
+    fn test_parse_db_operations() {
+        let content = FileContent((
+            String::from(
+                "Pallet: \"balances\", Extrinsic: \"transfer\", Lowest values: [], Highest values: [], Steps: [3], Repeat: 2\n\
+                u,extrinsic_time,storage_root_time,reads,repeat_reads,writes,repeat_writes\n\
+                1,100000,50000,2,0,3,0\n\
+                50,150000,60000,4,1,5,1",
+            ),
+            PathBuf::from(""),
+        ));
+
+        let (res, layout) = parse_header(&content).unwrap();
+        assert_eq!(res.input_var_names, vec!["u"]);
+        assert_eq!(
+            layout,
+            BodyLayout {
+                column_count: 7,
+                var_count: 1,
+                extrinsic_time: 1,
+                storage_root_time: 2,
+                reads: Some(3),
+                writes: Some(5),
+            }
+        );
+
+        let res = parse_body(&content, &layout).unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[1].input_vars, vec![50]);
+        assert_eq!(res[1].extrinsic_time, 150000);
+        assert_eq!(res[1].storage_root_time, 60000);
+        assert_eq!(res[1].reads, 4);
+        assert_eq!(res[1].writes, 5);
+    }
+
     #[test]
     fn test_parse_body() {
         let test_data = [
@@ -304,10 +440,9 @@ mod tests {
         for (content, output) in &test_data {
             let content = FileContent((String::from(*content), PathBuf::from("")));
             let expected_len = output[0].len();
-            let res = parse_body(&content, expected_len).unwrap();
+            let res = parse_body(&content, &BodyLayout::with_vars(expected_len - 2)).unwrap();
 
-            let mut counter = 0;
-            for entry in res {
+            for (counter, entry) in res.into_iter().enumerate() {
                 // println!("{:?}", entry);
 
                 // Hint: the other two values are `extrinsic_time`
@@ -315,13 +450,10 @@ mod tests {
                 assert_eq!(entry.input_vars.len(), expected_len - 2);
 
                 let current = &output[counter];
-                for i in 0..expected_len - 2 {
-                    assert_eq!(entry.input_vars[i], current[i]);
-                }
+                assert_eq!(entry.input_vars[..], current[..expected_len - 2]);
 
                 assert_eq!(entry.extrinsic_time, current[expected_len - 2]);
                 assert_eq!(entry.storage_root_time, current[expected_len - 1]);
-                counter += 1;
             }
         }
     }
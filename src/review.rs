@@ -0,0 +1,261 @@
+//! Review ledger: records which reviewer approved which extrinsic, so that the
+//! reports can tell approved extrinsics apart from changed and unreviewed ones.
+
+use crate::tables::{PerExtrTable, ReviewStatus, ReviewTable, ReviewTableEntry};
+use crate::{Baseline, ExtrinsicCollection, ExtrinsicId, ExtrinsicResult, Metric, RoundBy};
+use failure::Error;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Ledger file if none is specified.
+pub const DEFAULT_LEDGER_FILE: &str = "bench-reviews.json";
+
+/// Version of the ledger format, increased on incompatible changes.
+const LEDGER_VERSION: u32 = 1;
+
+#[allow(non_local_definitions)]
+mod error {
+    use crate::ExtrinsicId;
+
+    #[derive(Debug, Fail)]
+    pub(super) enum ReviewError {
+        #[fail(display = "ledger has unsupported version {}", 0)]
+        UnsupportedVersion(u32),
+        #[fail(display = "reviewer must not be empty")]
+        EmptyReviewer,
+        #[fail(display = "extrinsic {} is not part of the results", 0)]
+        UnknownExtrinsic(ExtrinsicId),
+    }
+}
+
+use error::ReviewError::*;
+
+/// The approvals of the reviewers, at most one per extrinsic.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReviewLedger {
+    version: u32,
+    approvals: Vec<Approval>,
+}
+
+/// Approval of an extrinsic, along with the results it was based on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Approval {
+    pub pallet: String,
+    pub extrinsic: String,
+    pub reviewer: String,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    /// Average extrinsic time.
+    pub average: f64,
+    /// Ratio of the average extrinsic time to the one of the fastest extrinsic.
+    pub ratio: f64,
+    /// FNV-1a hash of the result file, as hex.
+    pub file_hash: String,
+}
+
+impl Default for ReviewLedger {
+    fn default() -> Self {
+        ReviewLedger {
+            version: LEDGER_VERSION,
+            approvals: Vec::new(),
+        }
+    }
+}
+
+fn display_hash(result: &ExtrinsicResult) -> String {
+    format!("{:016x}", result.file_hash)
+}
+
+impl ReviewLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Reads the ledger at the given path, an empty one if the file does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::new());
+        }
+
+        let ledger: ReviewLedger = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        if ledger.version != LEDGER_VERSION {
+            return Err(UnsupportedVersion(ledger.version).into());
+        }
+
+        Ok(ledger)
+    }
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")?;
+        Ok(())
+    }
+    pub fn approval(&self, pallet: &str, extrinsic: &str) -> Option<&Approval> {
+        self.approvals
+            .iter()
+            .find(|a| a.pallet == pallet && a.extrinsic == extrinsic)
+    }
+    /// Approves the given extrinsics of the collection, or all of them if none are
+    /// given, on behalf of the reviewer. Previous approvals of the extrinsics are
+    /// replaced. Returns the amount of approved extrinsics.
+    pub fn sign_off(
+        &mut self,
+        collection: &ExtrinsicCollection,
+        reviewer: &str,
+        ids: &[ExtrinsicId],
+        timestamp: u64,
+    ) -> Result<usize, Error> {
+        if reviewer.trim().is_empty() {
+            return Err(EmptyReviewer.into());
+        }
+        if let Some(id) = ids
+            .iter()
+            .find(|id| !collection.results.iter().any(|result| id.matches(result)))
+        {
+            return Err(UnknownExtrinsic(id.clone()).into());
+        }
+
+        let ratios = collection.generate_ratio_table(&Baseline::Fastest(Metric::ExtrinsicTime))?;
+
+        let mut approved = 0;
+        for (pallet, extrinsic, average, _, ratio, ..) in ratios.raw_list() {
+            let result = match collection
+                .results
+                .iter()
+                .find(|r| r.pallet == pallet && r.extrinsic == extrinsic)
+            {
+                Some(result) => result,
+                None => continue,
+            };
+            if !ids.is_empty() && !ids.iter().any(|id| id.matches(result)) {
+                continue;
+            }
+
+            self.approvals
+                .retain(|a| a.pallet != pallet || a.extrinsic != extrinsic);
+            self.approvals.push(Approval {
+                pallet: pallet.to_string(),
+                extrinsic: extrinsic.to_string(),
+                reviewer: reviewer.to_string(),
+                timestamp,
+                average,
+                ratio,
+                file_hash: display_hash(result),
+            });
+            approved += 1;
+        }
+
+        self.approvals
+            .sort_by(|a, b| (&a.pallet, &a.extrinsic).cmp(&(&b.pallet, &b.extrinsic)));
+
+        Ok(approved)
+    }
+    /// Review status of the result: approved as long as its file did not change.
+    pub(crate) fn status(&self, result: &ExtrinsicResult) -> ReviewStatus {
+        match self.approval(&result.pallet, &result.extrinsic) {
+            Some(approval) if approval.file_hash == display_hash(result) => ReviewStatus::Approved,
+            Some(_) => ReviewStatus::Changed,
+            None => ReviewStatus::NeverReviewed,
+        }
+    }
+    /// Lists the review status of each extrinsic of the collection.
+    pub fn review<'a>(
+        &'a self,
+        collection: &'a ExtrinsicCollection,
+    ) -> Result<ReviewTable<'a>, Error> {
+        let ratios = collection.generate_ratio_table(&Baseline::Fastest(Metric::ExtrinsicTime))?;
+
+        let mut table = ReviewTable::new();
+        for (result, (_, _, average, _, ratio, ..)) in
+            collection.results.iter().zip(ratios.raw_list())
+        {
+            let approval = self.approval(&result.pallet, &result.extrinsic);
+            table.push(ReviewTableEntry {
+                pallet: &result.pallet,
+                extrinsic: &result.extrinsic,
+                status: self.status(result),
+                reviewer: approval.map(|a| a.reviewer.as_str()),
+                approved_at: approval.map(|a| a.timestamp),
+                approved_time: approval.map(|a| a.average),
+                current_time: average,
+                percentage: approval
+                    .map(|a| ((average - a.average) / a.average * 100.0).round_by(4)),
+                approved_ratio: approval.map(|a| a.ratio),
+                current_ratio: ratio,
+            });
+        }
+
+        Ok(table)
+    }
+    /// Marks the extrinsics of the table with their review status.
+    pub fn mark(&self, collection: &ExtrinsicCollection, table: &mut PerExtrTable) {
+        for result in &collection.results {
+            table.mark_review(&result.pallet, &result.extrinsic, self.status(result));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+
+    fn result(extrinsic: &str, time: u64, file_hash: u64) -> ExtrinsicResult {
+        let mut result = fixtures::result("balances", extrinsic, &[(time, 0)]);
+        result.file_hash = file_hash;
+        result
+    }
+
+    #[test]
+    fn test_sign_off() {
+        let mut collection = ExtrinsicCollection::new();
+        collection.push(result("transfer", 200, 1));
+        collection.push(result("set_balance", 100, 2));
+
+        let mut ledger = ReviewLedger::new();
+        let ids = vec!["balances.transfer".parse().unwrap()];
+        assert_eq!(ledger.sign_off(&collection, "alice", &ids, 0).unwrap(), 1);
+
+        let approval = ledger.approval("balances", "transfer").unwrap();
+        assert_eq!(approval.reviewer, "alice");
+        assert_eq!(approval.ratio, 2.0);
+        assert_eq!(approval.file_hash, "0000000000000001");
+
+        // Approving again replaces the previous approval
+        assert_eq!(ledger.sign_off(&collection, "bob", &[], 0).unwrap(), 2);
+        assert_eq!(ledger.approvals.len(), 2);
+        assert_eq!(
+            ledger.approval("balances", "transfer").unwrap().reviewer,
+            "bob"
+        );
+
+        let mut changed = ExtrinsicCollection::new();
+        changed.push(result("transfer", 220, 3));
+        changed.push(result("set_balance", 100, 2));
+        changed.push(result("transfer_all", 100, 4));
+
+        let statuses: Vec<ReviewStatus> = changed
+            .results
+            .iter()
+            .map(|result| ledger.status(result))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ReviewStatus::Changed,
+                ReviewStatus::Approved,
+                ReviewStatus::NeverReviewed
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sign_off_errors() {
+        let mut collection = ExtrinsicCollection::new();
+        collection.push(result("transfer", 200, 1));
+
+        let mut ledger = ReviewLedger::new();
+        assert!(ledger.sign_off(&collection, " ", &[], 0).is_err());
+        let ids = vec!["balances.unknown".parse().unwrap()];
+        assert!(ledger.sign_off(&collection, "alice", &ids, 0).is_err());
+        assert!(ledger.approvals.is_empty());
+    }
+}
@@ -0,0 +1,728 @@
+//! Statistical helpers used by the different reviews.
+
+/// Fits a straight line through the given `(x, y)` points using the least
+/// squares method. Returns the intercept and the slope, or `None` if there
+/// are less than two distinct `x` values.
+///
+/// # Example
+/// ```ignore
+/// let points = [(1.0, 3.0), (2.0, 5.0), (3.0, 7.0)];
+/// assert_eq!(Some((1.0, 2.0)), linear_regression(&points));
+/// ```
+pub(crate) fn linear_regression(points: &[(f64, f64)]) -> Option<(f64, f64)> {
+    if points.is_empty() {
+        return None;
+    }
+
+    let count = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / count;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / count;
+
+    let (mut covariance, mut variance) = (0.0, 0.0);
+    for (x, y) in points {
+        covariance += (x - mean_x) * (y - mean_y);
+        variance += (x - mean_x).powi(2);
+    }
+
+    if variance == 0.0 {
+        return None;
+    }
+
+    let slope = covariance / variance;
+    Some((mean_y - slope * mean_x, slope))
+}
+
+/// Fits a linear model `y = b0 + b1 * x1 + ... + bn * xn` through the given points
+/// using the least squares method. Each entry of `xs` contains the `n` variables
+/// of a point. Returns the coefficients `[b0, b1, ..., bn]`, or `None` if the
+/// variables are not independent of each other (e.g. one never varies).
+pub(crate) fn multiple_linear_regression(xs: &[Vec<f64>], ys: &[f64]) -> Option<Vec<f64>> {
+    let size = xs.first()?.len() + 1;
+
+    // Build the normal equations `(X^T * X) * b = X^T * y`, where `X` has an
+    // additional column of ones for the intercept.
+    let mut matrix = vec![vec![0.0; size + 1]; size];
+    for (x, y) in xs.iter().zip(ys) {
+        let row: Vec<f64> = std::iter::once(1.0).chain(x.iter().cloned()).collect();
+        for i in 0..size {
+            for j in 0..size {
+                matrix[i][j] += row[i] * row[j];
+            }
+            matrix[i][size] += row[i] * y;
+        }
+    }
+
+    // Gaussian elimination with partial pivoting.
+    for col in 0..size {
+        let pivot = (col..size).max_by(|&a, &b| {
+            matrix[a][col]
+                .abs()
+                .partial_cmp(&matrix[b][col].abs())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })?;
+
+        if matrix[pivot][col].abs() < 1e-9 {
+            return None;
+        }
+
+        matrix.swap(col, pivot);
+        let pivot_row = matrix[col].clone();
+        for (index, row) in matrix.iter_mut().enumerate() {
+            if index != col {
+                let factor = row[col] / pivot_row[col];
+                for (value, pivot_value) in row.iter_mut().zip(&pivot_row).skip(col) {
+                    *value -= factor * pivot_value;
+                }
+            }
+        }
+    }
+
+    Some((0..size).map(|i| matrix[i][size] / matrix[i][i]).collect())
+}
+
+/// Fits a linear model like `multiple_linear_regression`, but variables which never
+/// vary get a coefficient of zero instead of failing the fit. Their constant share
+/// is part of the intercept. Returns `None` if there are no points.
+pub(crate) fn fit_linear_model(xs: &[Vec<f64>], ys: &[f64]) -> Option<Vec<f64>> {
+    let var_count = xs.first()?.len();
+
+    let varying: Vec<usize> = (0..var_count)
+        .filter(|&index| xs.iter().any(|x| x[index] != xs[0][index]))
+        .collect();
+
+    let reduced: Vec<Vec<f64>> = xs
+        .iter()
+        .map(|x| varying.iter().map(|&index| x[index]).collect())
+        .collect();
+
+    let fitted = if varying.is_empty() {
+        vec![ys.iter().sum::<f64>() / ys.len() as f64]
+    } else {
+        multiple_linear_regression(&reduced, ys)?
+    };
+
+    let mut coefficients = vec![0.0; var_count + 1];
+    coefficients[0] = fitted[0];
+    for (position, &index) in varying.iter().enumerate() {
+        coefficients[index + 1] = fitted[position + 1];
+    }
+
+    Some(coefficients)
+}
+
+/// Returns the `p`-th percentile (nearest rank) of the given values, or `None`
+/// if there are no values.
+///
+/// # Example
+/// ```ignore
+/// assert_eq!(Some(4), percentile(&[5, 1, 4, 2, 3], 80.0));
+/// ```
+pub(crate) fn percentile(values: &[u64], p: f64) -> Option<u64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+
+    let rank = (p / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted.get(rank.max(1) - 1).cloned()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+/// Sample variance (with Bessel's correction).
+fn variance(values: &[f64]) -> f64 {
+    let mean = mean(values);
+    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() as f64 - 1.0)
+}
+
+/// Median of the values, the mean of the two middle values for an even count.
+pub(crate) fn median(values: &[f64]) -> Option<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+    let middle = sorted.len() / 2;
+    match sorted.len() {
+        0 => None,
+        len if len % 2 == 0 => Some((sorted[middle - 1] + sorted[middle]) / 2.0),
+        _ => Some(sorted[middle]),
+    }
+}
+
+/// Sample standard deviation, zero for less than two values.
+pub(crate) fn standard_deviation(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+
+    variance(values).sqrt()
+}
+
+/// Natural logarithm of the gamma function (Lanczos approximation).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000000000190015, |sum, (index, coefficient)| {
+            sum + coefficient / (x + 1.0 + index as f64)
+        });
+
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Continued fraction of the incomplete beta function, see `incomplete_beta`.
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 200;
+    const EPSILON: f64 = 3e-16;
+    const TINY: f64 = 1e-300;
+
+    let clamp = |value: f64| if value.abs() < TINY { TINY } else { value };
+
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut result = d;
+
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+
+        // Even step
+        let numerator = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + numerator * d);
+        c = clamp(1.0 + numerator / c);
+        result *= d * c;
+
+        // Odd step
+        let numerator = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + numerator * d);
+        c = clamp(1.0 + numerator / c);
+        let delta = d * c;
+        result *= delta;
+
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+
+    result
+}
+
+/// Regularized incomplete beta function `I_x(a, b)`.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+
+    // The continued fraction converges quickly for `x < (a + 1) / (a + b + 2)`,
+    // otherwise the symmetry `I_x(a, b) = 1 - I_(1-x)(b, a)` is used.
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Two-sided p-value of Student's t-distribution.
+fn t_test_p_value(t: f64, degrees_of_freedom: f64) -> f64 {
+    incomplete_beta(
+        degrees_of_freedom / 2.0,
+        0.5,
+        degrees_of_freedom / (degrees_of_freedom + t * t),
+    )
+}
+
+/// Complementary error function, with a fractional error below `1.2e-7`.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let result = t
+        * (-z * z - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+
+    if x >= 0.0 {
+        result
+    } else {
+        2.0 - result
+    }
+}
+
+/// Welch's t-test (unequal variances). Returns the two-sided p-value, or `None`
+/// if a sample contains less than two values. If neither sample varies, the
+/// p-value is `0.0` for different means and `1.0` for equal ones.
+pub(crate) fn welch_t_test(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+
+    let (var_a, var_b) = (variance(a) / a.len() as f64, variance(b) / b.len() as f64);
+    if var_a + var_b == 0.0 {
+        // Without any noise, a different mean is a certain change.
+        return Some(if mean(a) == mean(b) { 1.0 } else { 0.0 });
+    }
+
+    let t = (mean(a) - mean(b)) / (var_a + var_b).sqrt();
+    // Welch–Satterthwaite equation
+    let degrees_of_freedom = (var_a + var_b).powi(2)
+        / (var_a.powi(2) / (a.len() as f64 - 1.0) + var_b.powi(2) / (b.len() as f64 - 1.0));
+
+    Some(t_test_p_value(t, degrees_of_freedom))
+}
+
+/// Mann–Whitney U test, using the normal approximation with tie and continuity
+/// correction. Returns the two-sided p-value, or `None` if a sample is empty or
+/// all values are equal.
+pub(crate) fn mann_whitney_u(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+
+    // Rank all values, where tied values get the average of their ranks.
+    let mut values: Vec<(f64, bool)> = a
+        .iter()
+        .map(|&v| (v, true))
+        .chain(b.iter().map(|&v| (v, false)))
+        .collect();
+    values.sort_by(|x, y| x.0.partial_cmp(&y.0).unwrap_or(std::cmp::Ordering::Equal));
+
+    let (mut rank_sum_a, mut tie_correction) = (0.0, 0.0);
+    let mut start = 0;
+    while start < values.len() {
+        let end = (start..values.len())
+            .find(|&index| values[index].0 != values[start].0)
+            .unwrap_or(values.len());
+
+        let ties = (end - start) as f64;
+        let rank = (start + end + 1) as f64 / 2.0;
+        rank_sum_a += rank * values[start..end].iter().filter(|(_, in_a)| *in_a).count() as f64;
+        tie_correction += ties.powi(3) - ties;
+
+        start = end;
+    }
+
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let n = n_a + n_b;
+    let u = rank_sum_a - n_a * (n_a + 1.0) / 2.0;
+    let mean_u = n_a * n_b / 2.0;
+    let sigma = (n_a * n_b / 12.0 * ((n + 1.0) - tie_correction / (n * (n - 1.0)))).sqrt();
+
+    if sigma == 0.0 || !sigma.is_finite() {
+        return None;
+    }
+
+    let z = ((u - mean_u).abs() - 0.5).max(0.0) / sigma;
+    Some(erfc(z / std::f64::consts::SQRT_2).min(1.0))
+}
+
+/// Cohen's d of `b` compared to `a`, based on the pooled standard deviation.
+/// Returns `None` if a sample contains less than two values or neither sample
+/// varies.
+pub(crate) fn cohens_d(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+
+    let (n_a, n_b) = (a.len() as f64, b.len() as f64);
+    let pooled =
+        (((n_a - 1.0) * variance(a) + (n_b - 1.0) * variance(b)) / (n_a + n_b - 2.0)).sqrt();
+
+    if pooled == 0.0 {
+        return None;
+    }
+
+    Some((mean(b) - mean(a)) / pooled)
+}
+
+/// Cliff's delta of `b` compared to `a`: the probability that a value of `b` is
+/// larger than a value of `a`, minus the reverse. Ranges from `-1` to `1`.
+pub(crate) fn cliffs_delta(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.is_empty() || b.is_empty() {
+        return None;
+    }
+
+    let dominance: i64 = b
+        .iter()
+        .flat_map(|y| a.iter().map(move |x| (y > x) as i64 - (y < x) as i64))
+        .sum();
+
+    Some(dominance as f64 / (a.len() * b.len()) as f64)
+}
+
+/// Ranks of the values, starting at 1 for the lowest value. Tied values get the
+/// average of their ranks.
+///
+/// # Example
+/// ```ignore
+/// assert_eq!(ranks(&[30.0, 10.0, 20.0, 10.0]), vec![4.0, 1.5, 3.0, 1.5]);
+/// ```
+pub(crate) fn ranks(values: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&x, &y| {
+        values[x]
+            .partial_cmp(&values[y])
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let mut ranks = vec![0.0; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let end = (start..order.len())
+            .find(|&index| values[order[index]] != values[order[start]])
+            .unwrap_or(order.len());
+
+        let rank = (start + end + 1) as f64 / 2.0;
+        for &index in &order[start..end] {
+            ranks[index] = rank;
+        }
+
+        start = end;
+    }
+
+    ranks
+}
+
+/// Spearman's rank correlation coefficient of the paired values: the Pearson
+/// correlation of their ranks. Returns `None` if there are less than two pairs or
+/// the values of a sample are all equal.
+pub(crate) fn spearman(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() != b.len() || a.len() < 2 {
+        return None;
+    }
+
+    let (ranks_a, ranks_b) = (ranks(a), ranks(b));
+    let (mean_a, mean_b) = (mean(&ranks_a), mean(&ranks_b));
+
+    let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+    for (x, y) in ranks_a.iter().zip(&ranks_b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a).powi(2);
+        variance_b += (y - mean_b).powi(2);
+    }
+
+    if variance_a == 0.0 || variance_b == 0.0 {
+        return None;
+    }
+
+    Some(covariance / (variance_a * variance_b).sqrt())
+}
+
+/// Kendall's tau-b of the paired values: the share of concordant minus the share
+/// of discordant pairs, corrected for ties. Returns `None` if there are less than
+/// two pairs or the values of a sample are all equal.
+pub(crate) fn kendall_tau(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() != b.len() || a.len() < 2 {
+        return None;
+    }
+
+    let (mut concordance, mut untied_a, mut untied_b) = (0i64, 0i64, 0i64);
+    for i in 0..a.len() {
+        for j in i + 1..a.len() {
+            let sign_a = (a[i] < a[j]) as i64 - (a[i] > a[j]) as i64;
+            let sign_b = (b[i] < b[j]) as i64 - (b[i] > b[j]) as i64;
+            concordance += sign_a * sign_b;
+            untied_a += sign_a.abs();
+            untied_b += sign_b.abs();
+        }
+    }
+
+    if untied_a == 0 || untied_b == 0 {
+        return None;
+    }
+
+    Some(concordance as f64 / ((untied_a * untied_b) as f64).sqrt())
+}
+
+/// Standard deviation of the noise of a series, estimated from the median of the
+/// absolute differences between successive values. Unlike the standard deviation
+/// of the values, the estimate is robust against level shifts and single outliers.
+pub(crate) fn successive_difference_sigma(series: &[f64]) -> f64 {
+    let differences: Vec<f64> = series.windows(2).map(|w| (w[1] - w[0]).abs()).collect();
+
+    // The difference of two normal values has a standard deviation of `sigma * sqrt(2)`,
+    // of which the median absolute value is `0.6745` times.
+    median(&differences).unwrap_or(0.0) / (0.6745 * std::f64::consts::SQRT_2)
+}
+
+/// Detects level shifts of a series with binary segmentation: the series is
+/// split where the cumulative sum of the deviations from its mean peaks, if the
+/// medians of both segments differ by more than `threshold` standard errors
+/// (based on the noise `sigma`) and by at least `min_change`. Both segments are
+/// then searched the same way. Segments contain at least `min_size` values.
+///
+/// Comparing the medians prevents single outliers from being detected as shifts.
+///
+/// Returns the indices at which the new levels start, in ascending order.
+pub(crate) fn change_points(
+    series: &[f64],
+    sigma: f64,
+    threshold: f64,
+    min_change: f64,
+    min_size: usize,
+) -> Vec<usize> {
+    let mut points = vec![];
+    split_segment(
+        series,
+        0,
+        series.len(),
+        (sigma, threshold, min_change, min_size.max(1)),
+        &mut points,
+    );
+
+    points.sort_unstable();
+    points
+}
+
+fn split_segment(
+    series: &[f64],
+    start: usize,
+    end: usize,
+    config: (f64, f64, f64, usize),
+    points: &mut Vec<usize>,
+) {
+    let (sigma, threshold, min_change, min_size) = config;
+    if end - start < 2 * min_size {
+        return;
+    }
+
+    let segment = &series[start..end];
+    let segment_mean = mean(segment);
+
+    // The cumulative sum of the deviations peaks where the level shifts
+    let mut cusum = 0.0;
+    let mut best: Option<(usize, f64)> = None;
+    for (index, value) in segment.iter().enumerate().take(segment.len() - min_size) {
+        cusum += value - segment_mean;
+        let split = index + 1;
+        if split >= min_size && best.is_none_or(|(_, peak)| cusum.abs() > peak) {
+            best = Some((split, cusum.abs()));
+        }
+    }
+
+    let split = match best {
+        Some((split, _)) => split,
+        None => return,
+    };
+
+    let (left, right) = segment.split_at(split);
+    // Unwrapping is ok, since both segments contain at least `min_size` values
+    let difference = (median(right).unwrap() - median(left).unwrap()).abs();
+    // The standard error of the median is about 1.2533 times the one of the mean
+    let standard_error =
+        1.2533 * sigma * (1.0 / left.len() as f64 + 1.0 / right.len() as f64).sqrt();
+
+    if difference == 0.0 || difference < min_change || difference <= threshold * standard_error {
+        return;
+    }
+
+    points.push(start + split);
+    split_segment(series, start, start + split, config, points);
+    split_segment(series, start + split, end, config, points);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_regression() {
+        let points = [(1.0, 3.0), (2.0, 5.0), (3.0, 7.0)];
+        assert_eq!(Some((1.0, 2.0)), linear_regression(&points));
+
+        let points = [(0.0, 1.0), (10.0, 2.0), (0.0, 3.0), (10.0, 4.0)];
+        assert_eq!(Some((2.0, 0.1)), linear_regression(&points));
+
+        // Not enough distinct values
+        assert_eq!(None, linear_regression(&[(1.0, 3.0), (1.0, 5.0)]));
+        assert_eq!(None, linear_regression(&[]));
+    }
+
+    #[test]
+    fn test_multiple_linear_regression() {
+        // y = 10 + 2 * x1 + 3 * x2
+        let xs = vec![
+            vec![1.0, 10.0],
+            vec![5.0, 10.0],
+            vec![10.0, 10.0],
+            vec![10.0, 1.0],
+            vec![10.0, 5.0],
+        ];
+        let ys: Vec<f64> = xs.iter().map(|x| 10.0 + 2.0 * x[0] + 3.0 * x[1]).collect();
+
+        let coefficients = multiple_linear_regression(&xs, &ys).unwrap();
+        let expected = [10.0, 2.0, 3.0];
+        for (coefficient, expected) in coefficients.iter().zip(&expected) {
+            assert!((coefficient - expected).abs() < 1e-6);
+        }
+
+        // Second variable never varies
+        let xs = vec![vec![1.0, 10.0], vec![5.0, 10.0]];
+        assert_eq!(None, multiple_linear_regression(&xs, &[1.0, 2.0]));
+        assert_eq!(None, multiple_linear_regression(&[], &[]));
+    }
+
+    #[test]
+    fn test_fit_linear_model() {
+        // y = 10 + 2 * x1, where x2 never varies
+        let xs = vec![vec![1.0, 10.0], vec![5.0, 10.0], vec![9.0, 10.0]];
+        let ys = [12.0, 20.0, 28.0];
+        let coefficients = fit_linear_model(&xs, &ys).unwrap();
+        assert!((coefficients[0] - 10.0).abs() < 1e-6);
+        assert!((coefficients[1] - 2.0).abs() < 1e-6);
+        assert_eq!(coefficients[2], 0.0);
+
+        // No variables at all
+        let xs = vec![vec![], vec![]];
+        assert_eq!(Some(vec![15.0]), fit_linear_model(&xs, &[10.0, 20.0]));
+        assert_eq!(None, fit_linear_model(&[], &[]));
+    }
+    #[test]
+    fn test_percentile() {
+        let values: Vec<u64> = (1..=100).rev().collect();
+        assert_eq!(Some(99), percentile(&values, 99.0));
+        assert_eq!(Some(50), percentile(&values, 50.0));
+        assert_eq!(Some(1), percentile(&values, 0.0));
+        assert_eq!(Some(100), percentile(&values, 100.0));
+        assert_eq!(Some(7), percentile(&[7], 99.0));
+        assert_eq!(None, percentile(&[], 99.0));
+    }
+    #[test]
+    fn test_median_and_standard_deviation() {
+        assert_eq!(Some(3.0), median(&[5.0, 1.0, 3.0]));
+        assert_eq!(Some(2.5), median(&[4.0, 1.0, 3.0, 2.0]));
+        assert_eq!(None, median(&[]));
+
+        assert_eq!(2.0, standard_deviation(&[2.0, 4.0, 6.0]));
+        assert_eq!(0.0, standard_deviation(&[7.0]));
+    }
+    #[test]
+    fn test_successive_difference_sigma() {
+        assert_eq!(0.0, successive_difference_sigma(&[5.0, 5.0, 5.0]));
+        assert_eq!(0.0, successive_difference_sigma(&[5.0]));
+        // A single shift does not affect the estimate
+        let sigma = successive_difference_sigma(&[10.0, 11.0, 10.0, 11.0, 20.0, 21.0, 20.0]);
+        assert!((sigma - 1.0 / (0.6745 * std::f64::consts::SQRT_2)).abs() < 1e-9);
+    }
+    #[test]
+    fn test_change_points() {
+        let series = [100.0, 101.0, 99.0, 100.0, 120.0, 121.0, 119.0, 120.0];
+        assert_eq!(vec![4], change_points(&series, 1.0, 3.0, 0.0, 2));
+        // Too small compared to the minimal change
+        assert!(change_points(&series, 1.0, 3.0, 50.0, 2).is_empty());
+        // Too small compared to the noise
+        assert!(change_points(&series, 20.0, 3.0, 0.0, 2).is_empty());
+
+        let series = [100.0, 100.0, 100.0, 110.0, 110.0, 110.0, 90.0, 90.0, 90.0];
+        assert_eq!(vec![3, 6], change_points(&series, 1.0, 3.0, 0.0, 2));
+
+        // A single outlier is not a level shift
+        let series = [100.0, 101.0, 99.0, 150.0, 100.0, 101.0, 99.0];
+        assert!(change_points(&series, 1.0, 3.0, 0.0, 2).is_empty());
+
+        assert!(change_points(&[], 1.0, 3.0, 0.0, 2).is_empty());
+    }
+    #[test]
+    fn test_t_test_p_value() {
+        // The t-distribution with one degree of freedom is the Cauchy distribution.
+        assert!((t_test_p_value(1.0, 1.0) - 0.5).abs() < 1e-9);
+        // With two degrees of freedom: p = 1 - t / sqrt(2 + t^2)
+        assert!((t_test_p_value(2.0, 2.0) - (1.0 - 2.0 / 6.0_f64.sqrt())).abs() < 1e-9);
+        // Approaches the normal distribution
+        assert!((t_test_p_value(1.959964, 1e7) - 0.05).abs() < 1e-5);
+        assert_eq!(t_test_p_value(0.0, 10.0), 1.0);
+    }
+
+    #[test]
+    fn test_welch_t_test() {
+        let a = [10.0, 11.0, 9.0, 10.0];
+        let b = [20.0, 21.0, 19.0, 20.0];
+        assert!(welch_t_test(&a, &b).unwrap() < 0.001);
+        assert!((welch_t_test(&a, &a).unwrap() - 1.0).abs() < 1e-9);
+
+        // Means of 10 and 11, squared standard errors of 1/6: t = -sqrt(3) with
+        // 6 degrees of freedom
+        let b = [11.0, 12.0, 10.0, 11.0];
+        let expected = t_test_p_value(3.0_f64.sqrt(), 6.0);
+        assert!((welch_t_test(&a, &b).unwrap() - expected).abs() < 1e-9);
+
+        assert_eq!(welch_t_test(&[1.0], &b), None);
+        assert_eq!(welch_t_test(&[1.0, 1.0], &[2.0, 2.0]), Some(0.0));
+        assert_eq!(welch_t_test(&[1.0, 1.0], &[1.0, 1.0]), Some(1.0));
+    }
+
+    #[test]
+    fn test_mann_whitney_u() {
+        // Completely separated: U = 0, z = (12.5 - 0.5) / sqrt(25 * 11 / 12)
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        let b = [6.0, 7.0, 8.0, 9.0, 10.0];
+        assert!((mann_whitney_u(&a, &b).unwrap() - 0.012186).abs() < 1e-5);
+        assert_eq!(mann_whitney_u(&a, &b), mann_whitney_u(&b, &a));
+
+        // Identical samples
+        assert_eq!(mann_whitney_u(&a, &a), Some(1.0));
+
+        assert_eq!(mann_whitney_u(&[1.0, 1.0], &[1.0, 1.0]), None);
+        assert_eq!(mann_whitney_u(&[], &b), None);
+    }
+
+    #[test]
+    fn test_effect_sizes() {
+        let a = [1.0, 2.0, 3.0];
+        let b = [2.0, 3.0, 4.0];
+        assert_eq!(cohens_d(&a, &b), Some(1.0));
+        assert_eq!(cohens_d(&b, &a), Some(-1.0));
+        assert_eq!(cohens_d(&[1.0, 1.0], &[2.0, 2.0]), None);
+
+        // 6 of 9 pairs are larger, 1 is smaller
+        assert_eq!(cliffs_delta(&a, &b), Some(5.0 / 9.0));
+        assert_eq!(cliffs_delta(&a, &[10.0]), Some(1.0));
+        assert_eq!(cliffs_delta(&a, &[]), None);
+    }
+
+    #[test]
+    fn test_rank_correlation() {
+        assert_eq!(ranks(&[30.0, 10.0, 20.0, 10.0]), vec![4.0, 1.5, 3.0, 1.5]);
+
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(spearman(&a, &[10.0, 20.0, 30.0, 40.0, 50.0]), Some(1.0));
+        assert_eq!(spearman(&a, &[5.0, 4.0, 3.0, 2.0, 1.0]), Some(-1.0));
+        assert_eq!(kendall_tau(&a, &[10.0, 20.0, 30.0, 40.0, 50.0]), Some(1.0));
+        assert_eq!(kendall_tau(&a, &[5.0, 4.0, 3.0, 2.0, 1.0]), Some(-1.0));
+
+        // One swap of neighbours: 9 of 10 pairs are concordant, one is discordant
+        let b = [1.0, 3.0, 2.0, 4.0, 5.0];
+        assert_eq!(kendall_tau(&a, &b), Some(0.8));
+        // 1 - 6 * 2 / (5 * 24)
+        assert_eq!(
+            spearman(&a, &b).map(|rho| (rho * 1e9).round() / 1e9),
+            Some(0.9)
+        );
+
+        // Ties, see tau-b
+        let tau = kendall_tau(&[1.0, 2.0, 2.0, 3.0], &[1.0, 2.0, 3.0, 4.0]).unwrap();
+        assert!((tau - 5.0 / 30.0_f64.sqrt()).abs() < 1e-12);
+
+        assert_eq!(spearman(&[1.0, 1.0], &[1.0, 2.0]), None);
+        assert_eq!(kendall_tau(&[1.0], &[1.0]), None);
+    }
+}
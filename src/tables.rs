@@ -1,7 +1,190 @@
+use super::history::RunMetadata;
+use super::{Metric, RoundBy};
+use serde::Serialize;
+
+use prettytable::{color, Attr};
 use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
 use std::io::stdout;
 
-#[derive(Debug)]
+/// Displays the input variables of a step, e.g. `1, 1000`.
+fn display_slice(slice: &[u64]) -> String {
+    let mut s = String::new();
+
+    for i in slice {
+        s.push_str(&format!("{}, ", i));
+    }
+
+    s.pop(); // remove whitespace
+    s.pop(); // remove comma
+    s
+}
+
+/// Prints the table as a Markdown table, where the first row is the header.
+/// Line breaks within cells are replaced by spaces.
+fn print_markdown(table: &prettytable::Table) {
+    for (index, row) in table.row_iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|cell| cell.get_content().replace('\n', " ").replace('|', "\\|"))
+            .collect();
+        println!("| {} |", cells.join(" | "));
+
+        if index == 0 {
+            println!("|{}", "---|".repeat(cells.len()));
+        }
+    }
+}
+
+/// Formats an optional value, `-` if missing.
+fn display_option(value: Option<f64>) -> String {
+    value
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| String::from("-"))
+}
+
+/// Remembers the rows of the previously printed tables, in order to highlight the
+/// rows which were added or changed since then (see `--watch`).
+#[derive(Debug, Default)]
+pub struct RowHighlighter {
+    previous: Option<HashSet<Vec<String>>>,
+}
+
+impl RowHighlighter {
+    /// Prints the table, where the first row is the header. Nothing is
+    /// highlighted the first time.
+    fn print(&mut self, table: prettytable::Table) {
+        self.print_all(vec![table]);
+    }
+    /// Prints the tables one after another, like `print`. The rows of all
+    /// tables are remembered together.
+    fn print_all(&mut self, mut tables: Vec<prettytable::Table>) {
+        let content = |row: &prettytable::Row| -> Vec<String> {
+            row.iter().map(|cell| cell.get_content()).collect()
+        };
+
+        let rows: HashSet<Vec<String>> = tables
+            .iter()
+            .flat_map(|table| table.row_iter().skip(1).map(content))
+            .collect();
+        if let Some(previous) = &self.previous {
+            for table in &mut tables {
+                for row in table.row_iter_mut().skip(1) {
+                    if !previous.contains(&content(row)) {
+                        for cell in row.iter_mut() {
+                            cell.style(Attr::Bold);
+                            cell.style(Attr::ForegroundColor(color::YELLOW));
+                        }
+                    }
+                }
+            }
+        }
+
+        for table in tables {
+            table.printstd();
+        }
+        self.previous = Some(rows);
+    }
+}
+
+/// Formats seconds since the Unix epoch as UTC date, e.g. `2021-03-01`.
+pub(crate) fn display_date(timestamp: u64) -> String {
+    let days = (timestamp / 86_400) as i64;
+
+    // Civil date from the days since the epoch, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Formats seconds since the Unix epoch as UTC date and time, e.g.
+/// `2021-03-01 14:05`.
+fn display_timestamp(timestamp: u64) -> String {
+    let seconds = timestamp % 86_400;
+    format!(
+        "{} {:02}:{:02}",
+        display_date(timestamp),
+        seconds / 3_600,
+        seconds % 3_600 / 60
+    )
+}
+
+/// Statistical significance of a change between two runs, based on the raw times
+/// of the repeats. Values are `None` if they can not be calculated, e.g. because
+/// a run has too few repeats.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Significance {
+    /// p-value of Welch's t-test.
+    pub t_test_p: Option<f64>,
+    /// p-value of the Mann–Whitney U test.
+    pub mann_whitney_p: Option<f64>,
+    /// Cohen's d of the new run compared to the old one.
+    pub cohens_d: Option<f64>,
+    /// Cliff's delta of the new run compared to the old one.
+    pub cliffs_delta: Option<f64>,
+}
+
+impl Significance {
+    /// Whether both tests consider the change significant at the given level
+    /// (e.g. `0.05`). A t-test p-value of `0.0`, e.g. because the times differ
+    /// but do not vary, is significant on its own.
+    pub fn is_significant(&self, alpha: f64) -> bool {
+        match (self.t_test_p, self.mann_whitney_p) {
+            (Some(0.0), _) => true,
+            (Some(t_test_p), Some(mann_whitney_p)) => t_test_p < alpha && mann_whitney_p < alpha,
+            _ => false,
+        }
+    }
+}
+
+/// Row of the per-extrinsic table, see `PerExtrTable::raw_list`.
+pub type PerExtrRow<'a> = (
+    &'a str,
+    &'a str,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+);
+
+/// Row of the per-step table, see `StepIncrTable::raw_list`.
+pub type StepIncrRow<'a> = (
+    &'a str,
+    &'a str,
+    &'a [u64],
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+    f64,
+);
+
+/// Row of the per-component table, see `ComponentTable::raw_list`.
+pub type ComponentRow<'a> = (&'a str, &'a str, &'a str, Vec<u64>, f64, f64);
+
+/// Row of the weight table, see `WeightTable::raw_list`.
+pub type WeightRow<'a> = (&'a str, &'a str, u64, Vec<(&'a str, u64)>);
+
+#[derive(Debug, Default)]
 pub struct PerExtrTable<'a> {
     entries: Vec<PerExtrTableEntry<'a>>,
 }
@@ -12,8 +195,26 @@ pub(crate) struct PerExtrTableEntry<'a> {
     pub extrinsic: &'a str,
     pub avg_extrinsic_time: f64,
     pub avg_storage_root_time: f64,
+    pub avg_total_time: f64,
     pub ratio: f64,
     pub percentage: f64,
+    pub storage_root_ratio: f64,
+    pub storage_root_percentage: f64,
+    pub total_ratio: f64,
+    /// Extrinsic time at the highest component values.
+    pub worst_case_time: f64,
+    /// Review status, only shown if the reviews were marked.
+    pub review: Option<ReviewStatus>,
+}
+
+impl PerExtrTableEntry<'_> {
+    fn avg_time(&self, metric: Metric) -> f64 {
+        match metric {
+            Metric::ExtrinsicTime => self.avg_extrinsic_time,
+            Metric::StorageRootTime => self.avg_storage_root_time,
+            Metric::TotalTime => self.avg_total_time,
+        }
+    }
 }
 
 impl<'a> PerExtrTable<'a> {
@@ -26,8 +227,33 @@ impl<'a> PerExtrTable<'a> {
         self.entries.push(entry);
     }
     pub fn sort_by_ratio(&mut self) {
-        self.entries
-            .sort_by(|a, b| a.ratio.partial_cmp(&b.ratio).unwrap_or(Ordering::Equal));
+        self.sort_by(Metric::ExtrinsicTime);
+    }
+    /// Sorts the entries by the average time of the given metric, fastest first.
+    pub fn sort_by(&mut self, metric: Metric) {
+        self.entries.sort_by(|a, b| {
+            a.avg_time(metric)
+                .partial_cmp(&b.avg_time(metric))
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+    /// Marks the extrinsic with its review status, shown in an additional column.
+    pub(crate) fn mark_review(&mut self, pallet: &str, extrinsic: &str, status: ReviewStatus) {
+        for entry in self
+            .entries
+            .iter_mut()
+            .filter(|e| e.pallet == pallet && e.extrinsic == extrinsic)
+        {
+            entry.review = Some(status);
+        }
+    }
+    /// Sorts the entries by the worst case extrinsic time, fastest first.
+    pub fn sort_by_worst_case(&mut self) {
+        self.entries.sort_by(|a, b| {
+            a.worst_case_time
+                .partial_cmp(&b.worst_case_time)
+                .unwrap_or(Ordering::Equal)
+        });
     }
     /// Returns a list of the entries.
     ///
@@ -38,16 +264,20 @@ impl<'a> PerExtrTable<'a> {
     /// - average storage root time
     /// - ratio
     /// - percentage
+    /// - storage root ratio
+    /// - storage root percentage
+    /// - average total time
+    /// - total ratio
+    /// - worst case extrinsic time
     ///
     /// # Example output:
     /// ```
     /// vec![
-    ///     ("identity", "add_registrar", 1.0, 0.0),
-    ///     ("treasury", "tip_new", 1.8363, 83.6271),
-    ///     ("balances", "transfer", 2.4501, 145.0108),
+    ///     ("identity", "add_registrar", 76600.8, 43874.4, 1.0, 0.0, 1.0, 0.0, 120475.2, 1.0, 76600.8),
+    ///     ("treasury", "tip_new", 140659.8333, 61608.3333, 1.8363, 83.6271, 1.4042, 40.4198, 202268.1667, 1.6789, 140659.8333),
     /// ];
     /// ```
-    pub fn raw_list(&self) -> Vec<(&str, &str, f64, f64, f64, f64)> {
+    pub fn raw_list(&self) -> Vec<PerExtrRow<'_>> {
         self.entries
             .iter()
             .map(|e| {
@@ -58,6 +288,11 @@ impl<'a> PerExtrTable<'a> {
                     e.avg_storage_root_time,
                     e.ratio,
                     e.percentage,
+                    e.storage_root_ratio,
+                    e.storage_root_percentage,
+                    e.avg_total_time,
+                    e.total_ratio,
+                    e.worst_case_time,
                 )
             })
             .collect()
@@ -73,12 +308,29 @@ impl<'a> PerExtrTable<'a> {
             "Avg. Extrinsic\nTime",
             "Avg. Storage\nRoot Time",
             "Extrinsic Time\nRatio (1:x)",
-            "Extrinsic Time\nIncrease (%)"
+            "Extrinsic Time\nIncrease (%)",
+            "Storage Root Time\nRatio (1:x)",
+            "Storage Root Time\nIncrease (%)",
+            "Avg. Total\nTime",
+            "Total Time\nRatio (1:x)",
+            "Worst Case\nExtrinsic Time"
         ]);
+        let reviews = self.entries.iter().any(|e| e.review.is_some());
+        if reviews {
+            table[0].add_cell(cell!(bc->"Review"));
+        }
 
         // Body
-        for entry in self.raw_list() {
-            table.add_row(row![entry.0, entry.1, entry.2, entry.3, entry.4, entry.5,]);
+        for (entry, raw) in self.entries.iter().zip(self.raw_list()) {
+            let mut row =
+                row![raw.0, raw.1, raw.2, raw.3, raw.4, raw.5, raw.6, raw.7, raw.8, raw.9, raw.10,];
+            if reviews {
+                row.add_cell(cell!(entry
+                    .review
+                    .map(|status| status.to_string())
+                    .unwrap_or_else(|| String::from("-"))));
+            }
+            table.add_row(row);
         }
 
         table
@@ -86,12 +338,16 @@ impl<'a> PerExtrTable<'a> {
     pub fn print(&self) {
         self.build_table().printstd();
     }
+    /// Prints the table, highlighting the rows which changed since the last time.
+    pub fn print_highlighted(&self, highlighter: &mut RowHighlighter) {
+        highlighter.print(self.build_table());
+    }
     pub fn print_csv(&self) {
         self.build_table().to_csv(stdout()).unwrap();
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct StepIncrTable<'a> {
     entries: Vec<StepIncrTableEntry<'a>>,
 }
@@ -100,6 +356,7 @@ pub struct StepIncrTable<'a> {
 pub(crate) struct StepIncrTableEntry<'a> {
     pub pallet: &'a str,
     pub extrinsic: &'a str,
+    pub baseline: &'a [u64],
     pub step_incrs: Vec<StepIncr<'a>>,
 }
 
@@ -108,9 +365,29 @@ pub(crate) struct StepIncr<'a> {
     pub input_vars: &'a Vec<u64>,
     pub avg_extrinsic_time: f64,
     pub avg_storage_root_time: f64,
+    pub avg_total_time: f64,
     pub ratio: f64,
     pub extrinsic_incr_percentage: f64,
+    pub storage_root_ratio: f64,
     pub storage_root_incr_percentage: f64,
+    pub total_ratio: f64,
+}
+
+impl StepIncr<'_> {
+    pub(crate) fn avg_time(&self, metric: Metric) -> f64 {
+        match metric {
+            Metric::ExtrinsicTime => self.avg_extrinsic_time,
+            Metric::StorageRootTime => self.avg_storage_root_time,
+            Metric::TotalTime => self.avg_total_time,
+        }
+    }
+    fn ratio(&self, metric: Metric) -> f64 {
+        match metric {
+            Metric::ExtrinsicTime => self.ratio,
+            Metric::StorageRootTime => self.storage_root_ratio,
+            Metric::TotalTime => self.total_ratio,
+        }
+    }
 }
 
 impl<'a> StepIncrTable<'a> {
@@ -123,11 +400,15 @@ impl<'a> StepIncrTable<'a> {
         self.entries.push(entry);
     }
     pub fn sort_by_extrinsic_incr_percentage(&mut self) {
-        // Sort by increase percentages for each extrinsic
+        self.sort_by(Metric::ExtrinsicTime);
+    }
+    /// Sorts the steps of each extrinsic by the ratio of the given metric, highest first.
+    pub fn sort_by(&mut self, metric: Metric) {
+        // Sort by increase for each extrinsic
         for entry in &mut self.entries {
             entry.step_incrs.sort_by(|a, b| {
-                b.extrinsic_incr_percentage
-                    .partial_cmp(&a.extrinsic_incr_percentage)
+                b.ratio(metric)
+                    .partial_cmp(&a.ratio(metric))
                     .unwrap_or(Ordering::Equal)
             });
         }
@@ -135,7 +416,8 @@ impl<'a> StepIncrTable<'a> {
         // Additionally, sort by pallet name
         self.entries.sort_by(|a, b| a.pallet.cmp(b.pallet));
     }
-    /// Returns a list of the entries.
+    /// Returns a list of the entries, in the order of the displayed columns
+    /// (without the baseline, see `baselines`).
     ///
     /// Data ordered as:
     /// - pallet
@@ -143,21 +425,25 @@ impl<'a> StepIncrTable<'a> {
     /// - input variables
     /// - average extrinsic time
     /// - average storage root time
-    /// - percentage increase of extrinsic time compared to the lowest
-    /// - percentage increase of storage root time compared to the lowest
+    /// - ratio of extrinsic time compared to the baseline step
+    /// - percentage increase of extrinsic time compared to the baseline step
+    /// - percentage increase of storage root time compared to the baseline step
+    /// - ratio of storage root time compared to the baseline step
+    /// - average total time
+    /// - ratio of total time compared to the baseline step
     ///
     /// # Example output:
     /// ```
     /// vec![
-    ///     ("democracy", "propose", &vec![19], 135927.0, 106922.0, 1.0796, 7.9605, 27.1982),
-    ///     ("democracy", "propose", &vec![10], 130751.0, 89704.8, 1.0385, 3.8495, 6.7160),
-    ///     ("democracy", "propose", &vec![1], 125904.3333, 84059.3333, 1.0, 0.0, 0.0),
+    ///     ("democracy", "propose", &vec![19], 135927.0, 106922.0, 1.0796, 7.9605, 27.1982, 1.272, 242849.0, 1.1566),
+    ///     ("democracy", "propose", &vec![10], 130751.0, 89704.8, 1.0385, 3.8495, 6.7160, 1.0672, 220455.8, 1.05),
+    ///     ("democracy", "propose", &vec![1], 125904.3333, 84059.3333, 1.0, 0.0, 0.0, 1.0, 209963.6667, 1.0),
     /// ];
     /// ```
-    pub fn raw_list(&self) -> Vec<(&str, &str, &[u64], f64, f64, f64, f64, f64)> {
+    pub fn raw_list(&self) -> Vec<StepIncrRow<'_>> {
         self.entries
             .iter()
-            .map(|e| {
+            .flat_map(|e| {
                 e.step_incrs
                     .iter()
                     .map(|s| {
@@ -170,26 +456,158 @@ impl<'a> StepIncrTable<'a> {
                             s.ratio,
                             s.extrinsic_incr_percentage,
                             s.storage_root_incr_percentage,
+                            s.storage_root_ratio,
+                            s.avg_total_time,
+                            s.total_ratio,
                         )
                     })
-                    .collect::<Vec<(&str, &str, &[u64], f64, f64, f64, f64, f64)>>()
+                    .collect::<Vec<StepIncrRow>>()
             })
-            .flatten()
+            .collect()
+    }
+    /// Returns the input variables of the step which was used as the baseline
+    /// for each extrinsic.
+    ///
+    /// # Example output:
+    /// ```
+    /// vec![
+    ///     ("balances", "set_balance_killing", &vec![496, 1000]),
+    ///     ("democracy", "propose", &vec![1]),
+    /// ];
+    /// ```
+    pub fn baselines(&self) -> Vec<(&str, &str, &[u64])> {
+        self.entries
+            .iter()
+            .map(|e| (e.pallet, e.extrinsic, e.baseline))
             .collect()
     }
     fn build_table(&self) -> prettytable::Table {
-        fn display_slice(slice: &[u64]) -> String {
-            let mut s = String::new();
+        let mut table = prettytable::Table::new();
 
-            for i in slice {
-                s.push_str(&format!("{}, ", i));
-            }
+        // Header
+        table.add_row(row![
+            bc =>
+            "Pallet",
+            "Extrinsic",
+            "Variables",
+            "Baseline",
+            "Avg. Extrinsic\nTime",
+            "Avg. Storage\nRoot Time",
+            "Extrinsic Time\nRatio (1:x)",
+            "Extrinsic Time\nIncrease (%)",
+            "Storage Root Time\nIncrease (%)",
+            "Storage Root Time\nRatio (1:x)",
+            "Avg. Total\nTime",
+            "Total Time\nRatio (1:x)"
+        ]);
 
-            s.pop(); // remove whitespace
-            s.pop(); // remove comma
-            s
+        // Body
+        for entry in &self.entries {
+            for step in &entry.step_incrs {
+                table.add_row(row![
+                    entry.pallet,
+                    entry.extrinsic,
+                    display_slice(step.input_vars),
+                    display_slice(entry.baseline),
+                    step.avg_extrinsic_time,
+                    step.avg_storage_root_time,
+                    step.ratio,
+                    step.extrinsic_incr_percentage,
+                    step.storage_root_incr_percentage,
+                    step.storage_root_ratio,
+                    step.avg_total_time,
+                    step.total_ratio,
+                ]);
+            }
         }
 
+        table
+    }
+    pub fn print(&self) {
+        self.build_table().printstd();
+    }
+    /// Prints the table, highlighting the rows which changed since the last time.
+    pub fn print_highlighted(&self, highlighter: &mut RowHighlighter) {
+        highlighter.print(self.build_table());
+    }
+    pub fn print_csv(&self) {
+        self.build_table().to_csv(stdout()).unwrap();
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ComponentTable<'a> {
+    entries: Vec<ComponentTableEntry<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ComponentTableEntry<'a> {
+    pub pallet: &'a str,
+    pub extrinsic: &'a str,
+    pub component: &'a str,
+    pub extrinsic_slope: f64,
+    pub storage_root_slope: f64,
+    pub steps: Vec<ComponentStep<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ComponentStep<'a> {
+    pub input_vars: &'a [u64],
+    pub value: u64,
+    pub avg_extrinsic_time: f64,
+    pub avg_storage_root_time: f64,
+}
+
+impl<'a> ComponentTable<'a> {
+    pub fn new() -> Self {
+        ComponentTable {
+            entries: Vec::new(),
+        }
+    }
+    pub(crate) fn push(&mut self, entry: ComponentTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    pub fn sort_by_component(&mut self) {
+        self.entries.sort_by(|a, b| {
+            a.pallet
+                .cmp(b.pallet)
+                .then_with(|| a.extrinsic.cmp(b.extrinsic))
+                .then_with(|| a.component.cmp(b.component))
+        });
+    }
+    /// Returns a list of the component groups.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - component
+    /// - values of the component within the group
+    /// - marginal extrinsic time per unit
+    /// - marginal storage root time per unit
+    ///
+    /// # Example output:
+    /// ```
+    /// vec![
+    ///     ("balances", "set_balance_killing", "u", vec![1, 199, 496], -31.5865, -33.9903),
+    ///     ("democracy", "propose", "p", vec![1, 10, 19], 556.8148, 1270.1481),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<ComponentRow<'_>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                (
+                    e.pallet,
+                    e.extrinsic,
+                    e.component,
+                    e.steps.iter().map(|s| s.value).collect(),
+                    e.extrinsic_slope,
+                    e.storage_root_slope,
+                )
+            })
+            .collect()
+    }
+    fn build_table(&self, repeat_group_values: bool) -> prettytable::Table {
         let mut table = prettytable::Table::new();
 
         // Header
@@ -197,25 +615,140 @@ impl<'a> StepIncrTable<'a> {
             bc =>
             "Pallet",
             "Extrinsic",
+            "Component",
             "Variables",
             "Avg. Extrinsic\nTime",
             "Avg. Storage\nRoot Time",
-            "Extrinsic Time\nRatio (1:x)",
-            "Extrinsic Time\nIncrease (%)",
-            "Storage Root Time\nIncrease (%)"
+            "Extrinsic Time\nper Unit",
+            "Storage Root Time\nper Unit"
+        ]);
+
+        // Body, the group values are only displayed once for each group.
+        for entry in &self.entries {
+            for (index, step) in entry.steps.iter().enumerate() {
+                let (pallet, extrinsic, component, extrinsic_slope, storage_root_slope) =
+                    if index == 0 || repeat_group_values {
+                        (
+                            entry.pallet.to_string(),
+                            entry.extrinsic.to_string(),
+                            entry.component.to_string(),
+                            entry.extrinsic_slope.to_string(),
+                            entry.storage_root_slope.to_string(),
+                        )
+                    } else {
+                        Default::default()
+                    };
+
+                table.add_row(row![
+                    pallet,
+                    extrinsic,
+                    component,
+                    display_slice(step.input_vars),
+                    step.avg_extrinsic_time,
+                    step.avg_storage_root_time,
+                    extrinsic_slope,
+                    storage_root_slope,
+                ]);
+            }
+        }
+
+        table
+    }
+    pub fn print(&self) {
+        self.build_table(false).printstd();
+    }
+    /// Prints the table, highlighting the rows which changed since the last time.
+    pub fn print_highlighted(&self, highlighter: &mut RowHighlighter) {
+        highlighter.print(self.build_table(true));
+    }
+    pub fn print_csv(&self) {
+        self.build_table(true).to_csv(stdout()).unwrap();
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct WeightTable<'a> {
+    entries: Vec<WeightTableEntry<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct WeightTableEntry<'a> {
+    pub pallet: &'a str,
+    pub extrinsic: &'a str,
+    pub base_weight: u64,
+    pub component_weights: Vec<(&'a str, u64)>,
+}
+
+impl<'a> WeightTable<'a> {
+    pub fn new() -> Self {
+        WeightTable {
+            entries: Vec::new(),
+        }
+    }
+    pub(crate) fn push(&mut self, entry: WeightTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    pub fn sort_by_extrinsic(&mut self) {
+        self.entries.sort_by(|a, b| {
+            a.pallet
+                .cmp(b.pallet)
+                .then_with(|| a.extrinsic.cmp(b.extrinsic))
+        });
+    }
+    /// Returns a list of the entries.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - base weight
+    /// - weight per unit of each component
+    ///
+    /// # Example output:
+    /// ```
+    /// vec![
+    ///     ("balances", "set_balance_killing", 119708175, vec![("u", 0), ("e", 0)]),
+    ///     ("democracy", "propose", 125292630, vec![("p", 556815)]),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<WeightRow<'_>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                (
+                    e.pallet,
+                    e.extrinsic,
+                    e.base_weight,
+                    e.component_weights.clone(),
+                )
+            })
+            .collect()
+    }
+    fn build_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![
+            bc =>
+            "Pallet",
+            "Extrinsic",
+            "Base Weight",
+            "Component Weights\n(per Unit)"
         ]);
 
         // Body
-        for entry in self.raw_list() {
+        for entry in &self.entries {
+            let component_weights = entry
+                .component_weights
+                .iter()
+                .map(|(name, weight)| format!("{}: {}", name, weight))
+                .collect::<Vec<String>>()
+                .join(", ");
+
             table.add_row(row![
-                entry.0,
-                entry.1,
-                display_slice(entry.2),
-                entry.3,
-                entry.4,
-                entry.5,
-                entry.6,
-                entry.7,
+                entry.pallet,
+                entry.extrinsic,
+                entry.base_weight,
+                component_weights,
             ]);
         }
 
@@ -224,7 +757,1677 @@ impl<'a> StepIncrTable<'a> {
     pub fn print(&self) {
         self.build_table().printstd();
     }
+    /// Prints the table, highlighting the rows which changed since the last time.
+    pub fn print_highlighted(&self, highlighter: &mut RowHighlighter) {
+        highlighter.print(self.build_table());
+    }
     pub fn print_csv(&self) {
         self.build_table().to_csv(stdout()).unwrap();
     }
 }
+
+/// Whether a weight of a weight file covers the measured weight.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightStatus {
+    /// The weight is lower than the measured weight (dangerous).
+    UnderWeighted,
+    /// The weight is higher than the measured weight (wasteful).
+    OverWeighted,
+    Ok,
+}
+
+impl WeightStatus {
+    /// Determines the status based on the deviation of the weight from the measured
+    /// weight (in percent). Deviations within the tolerance are ok.
+    pub(crate) fn from_percentage(percentage: f64, tolerance: f64) -> Self {
+        if percentage < -tolerance {
+            WeightStatus::UnderWeighted
+        } else if percentage > tolerance {
+            WeightStatus::OverWeighted
+        } else {
+            WeightStatus::Ok
+        }
+    }
+}
+
+impl fmt::Display for WeightStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WeightStatus::UnderWeighted => write!(f, "under-weighted"),
+            WeightStatus::OverWeighted => write!(f, "over-weighted"),
+            WeightStatus::Ok => write!(f, "ok"),
+        }
+    }
+}
+
+/// Row of the weight comparison table, see `WeightComparisonTable::raw_list`.
+pub type WeightComparisonRow<'a> = (&'a str, &'a str, u64, u64, f64, WeightStatus);
+
+#[derive(Debug, Default)]
+pub struct WeightComparisonTable<'a> {
+    entries: Vec<WeightComparisonTableEntry<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct WeightComparisonTableEntry<'a> {
+    pub pallet: &'a str,
+    pub extrinsic: &'a str,
+    pub evaluated_at: Vec<(&'a str, u64)>,
+    pub file_weight: u64,
+    pub measured_weight: u64,
+    pub percentage: f64,
+    pub status: WeightStatus,
+}
+
+impl<'a> WeightComparisonTable<'a> {
+    pub fn new() -> Self {
+        WeightComparisonTable {
+            entries: Vec::new(),
+        }
+    }
+    pub(crate) fn push(&mut self, entry: WeightComparisonTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    /// Sorts by the deviation of the weight file, the most under-weighted first.
+    pub fn sort_by_percentage(&mut self) {
+        self.entries.sort_by(|a, b| {
+            a.percentage
+                .partial_cmp(&b.percentage)
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+    /// Removes the entries whose weights are within the tolerance.
+    pub fn retain_deviations(&mut self) {
+        self.entries.retain(|e| e.status != WeightStatus::Ok);
+    }
+    /// Returns a list of the entries.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - weight of the weight file
+    /// - measured weight
+    /// - deviation of the weight file from the measured weight (%)
+    /// - status
+    ///
+    /// # Example output:
+    /// ```ignore
+    /// vec![
+    ///     ("democracy", "propose", 115000000, 135869115, -15.3595, WeightStatus::UnderWeighted),
+    ///     ("balances", "set_balance_killing", 150000000, 119708175, 25.3048, WeightStatus::OverWeighted),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<WeightComparisonRow<'_>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                (
+                    e.pallet,
+                    e.extrinsic,
+                    e.file_weight,
+                    e.measured_weight,
+                    e.percentage,
+                    e.status,
+                )
+            })
+            .collect()
+    }
+    fn build_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![
+            bc =>
+            "Pallet",
+            "Extrinsic",
+            "Evaluated At",
+            "Weight File",
+            "Measured\nWeight",
+            "Difference (%)",
+            "Status"
+        ]);
+
+        // Body
+        for entry in &self.entries {
+            let evaluated_at = entry
+                .evaluated_at
+                .iter()
+                .map(|(name, value)| format!("{}: {}", name, value))
+                .collect::<Vec<String>>()
+                .join(", ");
+
+            table.add_row(row![
+                entry.pallet,
+                entry.extrinsic,
+                evaluated_at,
+                entry.file_weight,
+                entry.measured_weight,
+                entry.percentage,
+                entry.status,
+            ]);
+        }
+
+        table
+    }
+    pub fn print(&self) {
+        self.build_table().printstd();
+    }
+    /// Prints the table, highlighting the rows which changed since the last time.
+    pub fn print_highlighted(&self, highlighter: &mut RowHighlighter) {
+        highlighter.print(self.build_table());
+    }
+    pub fn print_csv(&self) {
+        self.build_table().to_csv(stdout()).unwrap();
+    }
+}
+
+/// Mismatch between the components of an extrinsic and the parameters of its
+/// weight function.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComponentFinding {
+    /// The component has no parameter.
+    Missing(String),
+    /// The parameter has no component.
+    Extra(String),
+    /// The parameter at the position of the component has a different name.
+    Renamed { from: String, to: String },
+    /// The extrinsic has no weight function.
+    MissingFunction,
+}
+
+impl fmt::Display for ComponentFinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ComponentFinding::Missing(name) => write!(f, "missing component '{}'", name),
+            ComponentFinding::Extra(name) => write!(f, "extra parameter '{}'", name),
+            ComponentFinding::Renamed { from, to } => {
+                write!(f, "renamed component '{}' -> '{}'", from, to)
+            }
+            ComponentFinding::MissingFunction => write!(f, "missing weight function"),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ComponentCheckTable<'a> {
+    entries: Vec<ComponentCheckTableEntry<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ComponentCheckTableEntry<'a> {
+    pub pallet: &'a str,
+    pub extrinsic: &'a str,
+    pub finding: ComponentFinding,
+}
+
+impl<'a> ComponentCheckTable<'a> {
+    pub fn new() -> Self {
+        ComponentCheckTable {
+            entries: Vec::new(),
+        }
+    }
+    pub(crate) fn push(&mut self, entry: ComponentCheckTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Sorts by pallet and extrinsic name.
+    pub fn sort_by_extrinsic(&mut self) {
+        self.entries
+            .sort_by(|a, b| (a.pallet, a.extrinsic).cmp(&(b.pallet, b.extrinsic)));
+    }
+    /// Returns a list of the entries.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - finding
+    ///
+    /// # Example output:
+    /// ```ignore
+    /// vec![
+    ///     ("balances", "transfer", ComponentFinding::Renamed { from: "x".to_string(), to: "e".to_string() }),
+    ///     ("staking", "bond_extra", ComponentFinding::MissingFunction),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<(&str, &str, &ComponentFinding)> {
+        self.entries
+            .iter()
+            .map(|e| (e.pallet, e.extrinsic, &e.finding))
+            .collect()
+    }
+    fn build_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![bc => "Pallet", "Extrinsic", "Finding"]);
+
+        // Body
+        for entry in &self.entries {
+            table.add_row(row![entry.pallet, entry.extrinsic, entry.finding]);
+        }
+
+        table
+    }
+    pub fn print(&self) {
+        self.build_table().printstd();
+    }
+    /// Prints the table, highlighting the rows which changed since the last time.
+    pub fn print_highlighted(&self, highlighter: &mut RowHighlighter) {
+        highlighter.print(self.build_table());
+    }
+    pub fn print_csv(&self) {
+        self.build_table().to_csv(stdout()).unwrap();
+    }
+}
+
+/// Row of the capacity table, see `CapacityTable::raw_list`.
+pub type CapacityRow<'a> = (&'a str, &'a str, f64, u64, f64, u64, f64, u64);
+
+#[derive(Debug, Default)]
+pub struct CapacityTable<'a> {
+    entries: Vec<CapacityTableEntry<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct CapacityTableEntry<'a> {
+    pub pallet: &'a str,
+    pub extrinsic: &'a str,
+    pub avg_time: f64,
+    pub avg_calls: u64,
+    pub p99_time: f64,
+    pub p99_calls: u64,
+    pub worst_case_time: f64,
+    pub worst_case_calls: u64,
+}
+
+impl<'a> CapacityTable<'a> {
+    pub fn new() -> Self {
+        CapacityTable {
+            entries: Vec::new(),
+        }
+    }
+    pub(crate) fn push(&mut self, entry: CapacityTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    /// Sorts by the amount of calls per block in the worst case, the fewest first.
+    pub fn sort_by_worst_case(&mut self) {
+        self.entries.sort_by(|a, b| {
+            a.worst_case_calls
+                .cmp(&b.worst_case_calls)
+                .then_with(|| (a.pallet, a.extrinsic).cmp(&(b.pallet, b.extrinsic)))
+        });
+    }
+    /// Returns a list of the entries.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - average time
+    /// - calls per block at the average time
+    /// - 99th percentile time
+    /// - calls per block at the 99th percentile time
+    /// - worst case time
+    /// - calls per block at the worst case time
+    ///
+    /// # Example output:
+    /// ```ignore
+    /// vec![
+    ///     ("democracy", "delegate", 1514997.45, 990, 2880319.0, 520, 2835334.1, 529),
+    ///     ("identity", "add_registrar", 82669.6368, 18144, 96384.0, 15562, 90736.8, 16531),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<CapacityRow<'_>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                (
+                    e.pallet,
+                    e.extrinsic,
+                    e.avg_time,
+                    e.avg_calls,
+                    e.p99_time,
+                    e.p99_calls,
+                    e.worst_case_time,
+                    e.worst_case_calls,
+                )
+            })
+            .collect()
+    }
+    fn build_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![
+            bc =>
+            "Pallet",
+            "Extrinsic",
+            "Avg. Time",
+            "Calls per Block\n(avg.)",
+            "P99 Time",
+            "Calls per Block\n(p99)",
+            "Worst Case Time",
+            "Calls per Block\n(worst case)"
+        ]);
+
+        // Body
+        for entry in &self.entries {
+            table.add_row(row![
+                entry.pallet,
+                entry.extrinsic,
+                entry.avg_time,
+                entry.avg_calls,
+                entry.p99_time,
+                entry.p99_calls,
+                entry.worst_case_time,
+                entry.worst_case_calls,
+            ]);
+        }
+
+        table
+    }
+    pub fn print(&self) {
+        self.build_table().printstd();
+    }
+    /// Prints the table, highlighting the rows which changed since the last time.
+    pub fn print_highlighted(&self, highlighter: &mut RowHighlighter) {
+        highlighter.print(self.build_table());
+    }
+    pub fn print_csv(&self) {
+        self.build_table().to_csv(stdout()).unwrap();
+    }
+}
+
+/// Row of the limit table, see `LimitTable::raw_list`.
+pub type LimitRow<'a> = (&'a str, &'a str, u64, f64, f64, bool);
+
+#[derive(Debug, Default)]
+pub struct LimitTable<'a> {
+    entries: Vec<LimitTableEntry<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct LimitTableEntry<'a> {
+    pub pallet: &'a str,
+    pub extrinsic: &'a str,
+    pub worst_case_weight: u64,
+    /// Share of the block limit, in percent. Not rounded, so that the ceiling is
+    /// checked exactly, see `display_share`.
+    pub share: f64,
+    /// Highest allowed share of the block limit, in percent.
+    pub ceiling: f64,
+}
+
+impl LimitTableEntry<'_> {
+    fn exceeds(&self) -> bool {
+        self.share > self.ceiling
+    }
+    fn display_share(&self) -> f64 {
+        self.share.round_by(4)
+    }
+}
+
+impl<'a> LimitTable<'a> {
+    pub fn new() -> Self {
+        LimitTable {
+            entries: Vec::new(),
+        }
+    }
+    pub(crate) fn push(&mut self, entry: LimitTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    /// Sorts by how far the extrinsics exceed their ceiling, the worst first.
+    pub fn sort_by_excess(&mut self) {
+        self.entries.sort_by(|a, b| {
+            (b.share - b.ceiling)
+                .partial_cmp(&(a.share - a.ceiling))
+                .unwrap_or(Ordering::Equal)
+        });
+    }
+    /// Amount of extrinsics which exceed their ceiling.
+    pub fn offender_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.exceeds()).count()
+    }
+    /// Returns a list of the entries.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - worst case weight
+    /// - share of the block limit (%)
+    /// - ceiling (%)
+    /// - whether the ceiling is exceeded
+    ///
+    /// # Example output:
+    /// ```ignore
+    /// vec![
+    ///     ("democracy", "delegate", 2835334100, 0.1418, 0.1, true),
+    ///     ("balances", "transfer", 185658600, 0.0093, 0.1, false),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<LimitRow<'_>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                (
+                    e.pallet,
+                    e.extrinsic,
+                    e.worst_case_weight,
+                    e.display_share(),
+                    e.ceiling,
+                    e.exceeds(),
+                )
+            })
+            .collect()
+    }
+    /// Builds a table of the extrinsics which exceed their ceiling.
+    fn build_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![
+            bc =>
+            "Pallet",
+            "Extrinsic",
+            "Worst Case\nWeight",
+            "Share of\nBlock (%)",
+            "Ceiling (%)"
+        ]);
+
+        // Body
+        for entry in self.entries.iter().filter(|e| e.exceeds()) {
+            table.add_row(row![
+                entry.pallet,
+                entry.extrinsic,
+                entry.worst_case_weight,
+                entry.display_share(),
+                entry.ceiling,
+            ]);
+        }
+
+        table
+    }
+    pub fn print(&self) {
+        self.build_table().printstd();
+    }
+    /// Prints the table, highlighting the rows which changed since the last time.
+    pub fn print_highlighted(&self, highlighter: &mut RowHighlighter) {
+        highlighter.print(self.build_table());
+    }
+    pub fn print_csv(&self) {
+        self.build_table().to_csv(stdout()).unwrap();
+    }
+    /// Renders the checks as JUnit XML, with one test case per extrinsic. Test
+    /// cases of extrinsics which exceed their ceiling contain a failure.
+    pub fn to_junit(&self) -> String {
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuite name=\"check-limits\" tests=\"{}\" failures=\"{}\">\n",
+            self.entries.len(),
+            self.offender_count()
+        ));
+
+        for entry in &self.entries {
+            let test_case = format!(
+                "  <testcase classname=\"{}\" name=\"{}\"",
+                escape_xml(entry.pallet),
+                escape_xml(entry.extrinsic)
+            );
+
+            if entry.exceeds() {
+                xml.push_str(&format!(
+                    "{}>\n    <failure message=\"worst case weight {} takes {}% of the block, ceiling is {}%\"/>\n  </testcase>\n",
+                    test_case,
+                    entry.worst_case_weight,
+                    entry.display_share(),
+                    entry.ceiling
+                ));
+            } else {
+                xml.push_str(&format!("{}/>\n", test_case));
+            }
+        }
+
+        xml.push_str("</testsuite>\n");
+        xml
+    }
+}
+
+/// Escapes the special characters of XML attribute values.
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Row of the comparison table, see `ComparisonTable::raw_list`.
+pub type ComparisonRow<'a> = (
+    &'a str,
+    &'a str,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+);
+
+#[derive(Debug, Default)]
+pub struct ComparisonTable<'a> {
+    entries: Vec<ComparisonTableEntry<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ComparisonTableEntry<'a> {
+    pub pallet: &'a str,
+    pub extrinsic: &'a str,
+    /// Average time of the old run, `None` if the extrinsic was added.
+    pub old_time: Option<f64>,
+    /// Average time of the new run, `None` if the extrinsic was removed.
+    pub new_time: Option<f64>,
+    pub change: Option<f64>,
+    pub percentage: Option<f64>,
+    pub significance: Significance,
+}
+
+impl ComparisonTableEntry<'_> {
+    fn status(&self) -> &'static str {
+        match (self.old_time, self.new_time) {
+            (None, _) => "added",
+            (_, None) => "removed",
+            _ => "",
+        }
+    }
+}
+
+impl<'a> ComparisonTable<'a> {
+    pub fn new() -> Self {
+        ComparisonTable {
+            entries: Vec::new(),
+        }
+    }
+    pub(crate) fn push(&mut self, entry: ComparisonTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    pub(crate) fn contains(&self, pallet: &str, extrinsic: &str) -> bool {
+        self.entries
+            .iter()
+            .any(|e| e.pallet == pallet && e.extrinsic == extrinsic)
+    }
+    /// Removes the extrinsics whose change is not significant at the given level,
+    /// see `Significance::is_significant`. Added and removed extrinsics are kept.
+    pub fn retain_significant(&mut self, alpha: f64) {
+        self.entries.retain(|e| {
+            e.old_time.is_none() || e.new_time.is_none() || e.significance.is_significant(alpha)
+        });
+    }
+    /// Sorts by the change (%), the highest increase first. Added and removed
+    /// extrinsics are moved to the end.
+    pub fn sort_by_change(&mut self) {
+        self.entries
+            .sort_by(|a, b| match (a.percentage, b.percentage) {
+                (Some(a), Some(b)) => b.partial_cmp(&a).unwrap_or(Ordering::Equal),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => {
+                    (a.status(), a.pallet, a.extrinsic).cmp(&(b.status(), b.pallet, b.extrinsic))
+                }
+            });
+    }
+    /// Returns a list of the entries.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - old average time (`None` if added)
+    /// - new average time (`None` if removed)
+    /// - change
+    /// - change (%)
+    ///
+    /// # Example output:
+    /// ```ignore
+    /// vec![
+    ///     ("treasury", "tip_new", Some(140659.8333), Some(176963.0696), Some(36303.2362), Some(25.8092)),
+    ///     ("balances", "set_balance_killing", None, Some(114227.0), None, None),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<ComparisonRow<'_>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                (
+                    e.pallet,
+                    e.extrinsic,
+                    e.old_time,
+                    e.new_time,
+                    e.change,
+                    e.percentage,
+                )
+            })
+            .collect()
+    }
+    /// Returns the significance of each entry, in the same order as `raw_list`.
+    pub fn raw_significance(&self) -> Vec<Significance> {
+        self.entries.iter().map(|e| e.significance).collect()
+    }
+    fn build_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![
+            bc =>
+            "Pallet",
+            "Extrinsic",
+            "Old Avg.\nTime",
+            "New Avg.\nTime",
+            "Change",
+            "Change (%)",
+            "p-value\n(Welch)",
+            "p-value\n(Mann-Whitney)",
+            "Cohen's d",
+            "Cliff's\ndelta",
+            "Status"
+        ]);
+
+        // Body
+        for entry in &self.entries {
+            table.add_row(row![
+                entry.pallet,
+                entry.extrinsic,
+                display_option(entry.old_time),
+                display_option(entry.new_time),
+                display_option(entry.change),
+                display_option(entry.percentage),
+                display_option(entry.significance.t_test_p),
+                display_option(entry.significance.mann_whitney_p),
+                display_option(entry.significance.cohens_d),
+                display_option(entry.significance.cliffs_delta),
+                entry.status(),
+            ]);
+        }
+
+        table
+    }
+    pub fn print(&self) {
+        self.build_table().printstd();
+    }
+    /// Prints the table, highlighting the rows which changed since the last time.
+    pub fn print_highlighted(&self, highlighter: &mut RowHighlighter) {
+        highlighter.print(self.build_table());
+    }
+    pub fn print_csv(&self) {
+        self.build_table().to_csv(stdout()).unwrap();
+    }
+    pub fn print_markdown(&self) {
+        print_markdown(&self.build_table());
+    }
+}
+
+/// Row of the step comparison table, see `StepComparisonTable::raw_list`.
+pub type StepChangeRow<'a> = (
+    &'a str,
+    &'a str,
+    &'a [u64],
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+);
+
+/// Row of the marginal costs, see `StepComparisonTable::raw_costs`.
+pub type CostChangeRow<'a> = (&'a str, &'a str, &'a str, f64, f64, Option<f64>);
+
+#[derive(Debug, Default)]
+pub struct StepComparisonTable<'a> {
+    entries: Vec<StepComparisonTableEntry<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct StepComparisonTableEntry<'a> {
+    pub pallet: &'a str,
+    pub extrinsic: &'a str,
+    pub steps: Vec<StepChange<'a>>,
+    /// Base cost followed by the marginal cost of each component. Empty if the
+    /// runs have different components.
+    pub costs: Vec<CostChange<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct StepChange<'a> {
+    pub input_vars: &'a [u64],
+    /// Average time of the old run, `None` if the step was added.
+    pub old_time: Option<f64>,
+    /// Average time of the new run, `None` if the step was removed.
+    pub new_time: Option<f64>,
+    pub percentage: Option<f64>,
+    pub significance: Significance,
+}
+
+#[derive(Debug)]
+pub(crate) struct CostChange<'a> {
+    /// `base` or the name of the component.
+    pub name: &'a str,
+    pub old_cost: f64,
+    pub new_cost: f64,
+    /// `None` if the old cost is zero.
+    pub percentage: Option<f64>,
+}
+
+impl<'a> StepComparisonTable<'a> {
+    pub fn new() -> Self {
+        StepComparisonTable {
+            entries: Vec::new(),
+        }
+    }
+    pub(crate) fn push(&mut self, entry: StepComparisonTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    /// Sorts by pallet and extrinsic name.
+    pub fn sort_by_extrinsic(&mut self) {
+        self.entries
+            .sort_by(|a, b| (a.pallet, a.extrinsic).cmp(&(b.pallet, b.extrinsic)));
+    }
+    /// Removes the steps whose change is not significant at the given level, see
+    /// `Significance::is_significant`. Added and removed steps are kept.
+    pub fn retain_significant(&mut self, alpha: f64) {
+        for entry in &mut self.entries {
+            entry.steps.retain(|step| {
+                step.old_time.is_none()
+                    || step.new_time.is_none()
+                    || step.significance.is_significant(alpha)
+            });
+        }
+    }
+    /// Returns a list of the steps, ordered by their input variables.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - input vars
+    /// - old average time (`None` if added)
+    /// - new average time (`None` if removed)
+    /// - change (%)
+    ///
+    /// # Example output:
+    /// ```ignore
+    /// vec![
+    ///     ("balances", "transfer", &[20], Some(1200.0), Some(1400.0), Some(16.6667)),
+    ///     ("balances", "transfer", &[30], None, Some(1600.0), None),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<StepChangeRow<'_>> {
+        let mut list = vec![];
+
+        for entry in &self.entries {
+            for step in &entry.steps {
+                list.push((
+                    entry.pallet,
+                    entry.extrinsic,
+                    step.input_vars,
+                    step.old_time,
+                    step.new_time,
+                    step.percentage,
+                ));
+            }
+        }
+
+        list
+    }
+    /// Returns a list of the base cost and the marginal cost of each component.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - `base` or component name
+    /// - old cost
+    /// - new cost
+    /// - change (%)
+    ///
+    /// # Example output:
+    /// ```ignore
+    /// vec![
+    ///     ("balances", "transfer", "base", 1000.0, 1000.0, Some(0.0)),
+    ///     ("balances", "transfer", "u", 10.0, 20.0, Some(100.0)),
+    /// ];
+    /// ```
+    pub fn raw_costs(&self) -> Vec<CostChangeRow<'_>> {
+        let mut list = vec![];
+
+        for entry in &self.entries {
+            for cost in &entry.costs {
+                list.push((
+                    entry.pallet,
+                    entry.extrinsic,
+                    cost.name,
+                    cost.old_cost,
+                    cost.new_cost,
+                    cost.percentage,
+                ));
+            }
+        }
+
+        list
+    }
+    /// Returns the significance of each step, in the same order as `raw_list`.
+    pub fn raw_significance(&self) -> Vec<Significance> {
+        self.entries
+            .iter()
+            .flat_map(|entry| entry.steps.iter().map(|step| step.significance))
+            .collect()
+    }
+    fn build_step_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![
+            bc =>
+            "Pallet",
+            "Extrinsic",
+            "Input Vars",
+            "Old Avg.\nTime",
+            "New Avg.\nTime",
+            "Change (%)",
+            "p-value\n(Welch)",
+            "p-value\n(Mann-Whitney)",
+            "Cohen's d",
+            "Cliff's\ndelta"
+        ]);
+
+        // Body
+        for (entry, significance) in self.raw_list().into_iter().zip(self.raw_significance()) {
+            table.add_row(row![
+                entry.0,
+                entry.1,
+                display_slice(entry.2),
+                display_option(entry.3),
+                display_option(entry.4),
+                display_option(entry.5),
+                display_option(significance.t_test_p),
+                display_option(significance.mann_whitney_p),
+                display_option(significance.cohens_d),
+                display_option(significance.cliffs_delta),
+            ]);
+        }
+
+        table
+    }
+    fn build_cost_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![
+            bc =>
+            "Pallet",
+            "Extrinsic",
+            "Cost",
+            "Old",
+            "New",
+            "Change (%)"
+        ]);
+
+        // Body
+        for entry in self.raw_costs() {
+            table.add_row(row![
+                entry.0,
+                entry.1,
+                entry.2,
+                entry.3,
+                entry.4,
+                display_option(entry.5),
+            ]);
+        }
+
+        table
+    }
+    /// Prints the steps, followed by the base and marginal costs.
+    pub fn print(&self) {
+        self.build_step_table().printstd();
+        self.build_cost_table().printstd();
+    }
+    /// Prints the table, highlighting the rows which changed since the last time.
+    pub fn print_highlighted(&self, highlighter: &mut RowHighlighter) {
+        highlighter.print_all(vec![self.build_step_table(), self.build_cost_table()]);
+    }
+    pub fn print_csv(&self) {
+        self.build_step_table().to_csv(stdout()).unwrap();
+        println!();
+        self.build_cost_table().to_csv(stdout()).unwrap();
+    }
+    pub fn print_markdown(&self) {
+        print_markdown(&self.build_step_table());
+        println!();
+        print_markdown(&self.build_cost_table());
+    }
+}
+
+/// Row of the trend table, see `TrendTable::raw_list`.
+pub type TrendRow<'a> = (&'a str, u64, f64, f64, f64, Option<f64>, Option<f64>);
+
+#[derive(Debug, Default)]
+pub struct TrendTable<'a> {
+    entries: Vec<TrendTableEntry<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct TrendTableEntry<'a> {
+    pub metadata: &'a RunMetadata,
+    pub average: f64,
+    pub median: f64,
+    pub std_dev: f64,
+    /// Change of the average from the previous run (%), `None` for the first run.
+    pub change_previous: Option<f64>,
+    /// Change of the average from the first run (%), `None` for the first run.
+    pub change_first: Option<f64>,
+}
+
+impl<'a> TrendTable<'a> {
+    pub fn new() -> Self {
+        TrendTable {
+            entries: Vec::new(),
+        }
+    }
+    pub(crate) fn push(&mut self, entry: TrendTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Returns a list of the entries, in the order the runs were recorded.
+    ///
+    /// Data ordered as:
+    /// - label
+    /// - timestamp
+    /// - average time
+    /// - median time
+    /// - standard deviation
+    /// - change from the previous run (%)
+    /// - change from the first run (%)
+    ///
+    /// # Example output:
+    /// ```ignore
+    /// vec![
+    ///     ("a1b2c3", 1614607500, 187680.2, 180597.0, 14930.2564, None, None),
+    ///     ("d4e5f6", 1614693900, 184602.4227, 182047.0, 10317.6132, Some(-1.6399), Some(-1.6399)),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<TrendRow<'_>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                (
+                    e.metadata.label.as_str(),
+                    e.metadata.timestamp,
+                    e.average,
+                    e.median,
+                    e.std_dev,
+                    e.change_previous,
+                    e.change_first,
+                )
+            })
+            .collect()
+    }
+    fn build_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![
+            bc =>
+            "Run",
+            "Recorded\n(UTC)",
+            "Machine",
+            "Avg. Time",
+            "Median Time",
+            "Std. Dev.",
+            "Change from\nPrevious (%)",
+            "Change from\nFirst (%)"
+        ]);
+
+        // Body
+        for entry in &self.entries {
+            table.add_row(row![
+                entry.metadata.label,
+                display_timestamp(entry.metadata.timestamp),
+                entry.metadata.machine.as_deref().unwrap_or("-"),
+                entry.average,
+                entry.median,
+                entry.std_dev,
+                display_option(entry.change_previous),
+                display_option(entry.change_first),
+            ]);
+        }
+
+        table
+    }
+    pub fn print(&self) {
+        self.build_table().printstd();
+    }
+    pub fn print_csv(&self) {
+        self.build_table().to_csv(stdout()).unwrap();
+    }
+    /// Renders the entries as a JSON array, including the full metadata of
+    /// each run.
+    pub fn to_json(&self) -> String {
+        // Serializing the entries can not fail, all keys are strings
+        serde_json::to_string_pretty(&self.entries).unwrap()
+    }
+}
+
+/// Kind of a change detected in the history of an extrinsic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    /// The level of the times changed, starting with the run.
+    Shift,
+    /// The times of a single run deviate from the level.
+    Anomaly,
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChangeKind::Shift => write!(f, "shift"),
+            ChangeKind::Anomaly => write!(f, "anomaly"),
+        }
+    }
+}
+
+/// Row of the change table, see `ChangeTable::raw_list`.
+pub type ChangeRow<'a> = (
+    &'a str,
+    &'a str,
+    &'a str,
+    ChangeKind,
+    f64,
+    f64,
+    Option<f64>,
+    f64,
+);
+
+#[derive(Debug, Default)]
+pub struct ChangeTable<'a> {
+    entries: Vec<ChangeTableEntry<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ChangeTableEntry<'a> {
+    pub pallet: &'a str,
+    pub extrinsic: &'a str,
+    /// Run in which the shift starts, or which is anomalous.
+    pub metadata: &'a RunMetadata,
+    /// Position of the run in the history, used for sorting.
+    pub run_index: usize,
+    pub kind: ChangeKind,
+    /// Level before the shift, or the level the anomaly deviates from.
+    pub before: f64,
+    /// Level after the shift, or the average time of the anomalous run.
+    pub after: f64,
+    /// `None` if the level before is zero.
+    pub percentage: Option<f64>,
+    /// Size of the change in standard deviations of the noise.
+    pub score: f64,
+}
+
+impl<'a> ChangeTable<'a> {
+    pub fn new() -> Self {
+        ChangeTable {
+            entries: Vec::new(),
+        }
+    }
+    pub(crate) fn push(&mut self, entry: ChangeTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Sorts by extrinsic, followed by the order of the runs.
+    pub fn sort_by_extrinsic(&mut self) {
+        self.entries.sort_by(|a, b| {
+            (a.pallet, a.extrinsic, a.run_index).cmp(&(b.pallet, b.extrinsic, b.run_index))
+        });
+    }
+    /// Returns a list of the entries.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - label of the run
+    /// - kind of the change
+    /// - level before
+    /// - level after (or time of the anomalous run)
+    /// - change (%), `None` if the level before is zero
+    /// - score (standard deviations of the noise)
+    ///
+    /// # Example output:
+    /// ```ignore
+    /// vec![
+    ///     ("balances", "transfer", "d4e5f6", ChangeKind::Shift, 184602.4227, 211000.0, Some(14.2997), 25.3333),
+    ///     ("balances", "transfer", "g7h8i9", ChangeKind::Anomaly, 211000.0, 250000.0, Some(18.4834), 37.4335),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<ChangeRow<'_>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                (
+                    e.pallet,
+                    e.extrinsic,
+                    e.metadata.label.as_str(),
+                    e.kind,
+                    e.before,
+                    e.after,
+                    e.percentage,
+                    e.score,
+                )
+            })
+            .collect()
+    }
+    fn build_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![
+            bc =>
+            "Pallet",
+            "Extrinsic",
+            "Run",
+            "Recorded\n(UTC)",
+            "Kind",
+            "Before",
+            "After",
+            "Change (%)",
+            "Score"
+        ]);
+
+        // Body
+        for entry in &self.entries {
+            table.add_row(row![
+                entry.pallet,
+                entry.extrinsic,
+                entry.metadata.label,
+                display_timestamp(entry.metadata.timestamp),
+                entry.kind,
+                entry.before,
+                entry.after,
+                display_option(entry.percentage),
+                entry.score,
+            ]);
+        }
+
+        table
+    }
+    pub fn print(&self) {
+        self.build_table().printstd();
+    }
+    pub fn print_csv(&self) {
+        self.build_table().to_csv(stdout()).unwrap();
+    }
+}
+
+/// Whether a deviation from the baseline is explained by the allowlist.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DeviationStatus {
+    Unexplained,
+    /// Explained by an accepted deviation of the allowlist.
+    Accepted,
+    /// Matches an accepted deviation whose expiry date has passed.
+    Expired,
+}
+
+impl fmt::Display for DeviationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeviationStatus::Unexplained => write!(f, "unexplained"),
+            DeviationStatus::Accepted => write!(f, "accepted"),
+            DeviationStatus::Expired => write!(f, "expired"),
+        }
+    }
+}
+
+/// Row of the baseline check table, see `BaselineCheckTable::raw_list`.
+pub type BaselineCheckRow<'a> = (
+    &'a str,
+    &'a str,
+    Option<&'a [u64]>,
+    Option<f64>,
+    Option<f64>,
+    Option<f64>,
+    DeviationStatus,
+);
+
+#[derive(Debug, Default)]
+pub struct BaselineCheckTable<'a> {
+    entries: Vec<BaselineCheckTableEntry<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct BaselineCheckTableEntry<'a> {
+    pub pallet: &'a str,
+    pub extrinsic: &'a str,
+    /// Input variables of the deviating step, `None` for the whole extrinsic.
+    pub input_vars: Option<&'a [u64]>,
+    /// Average time of the baseline, `None` if the extrinsic (or step) is new.
+    pub baseline_time: Option<f64>,
+    /// Average time of the run, `None` if the extrinsic (or step) is missing.
+    pub current_time: Option<f64>,
+    pub percentage: Option<f64>,
+    pub status: DeviationStatus,
+    /// Reason of the accepted deviation, or its expiry date.
+    pub reason: Option<&'a str>,
+}
+
+impl<'a> BaselineCheckTable<'a> {
+    pub fn new() -> Self {
+        BaselineCheckTable {
+            entries: Vec::new(),
+        }
+    }
+    pub(crate) fn push(&mut self, entry: BaselineCheckTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Sorts by extrinsic, the deviation of the whole extrinsic before the ones
+    /// of its steps.
+    pub fn sort_by_extrinsic(&mut self) {
+        self.entries.sort_by(|a, b| {
+            (a.pallet, a.extrinsic, a.input_vars).cmp(&(b.pallet, b.extrinsic, b.input_vars))
+        });
+    }
+    /// Amount of deviations which are not explained by the allowlist.
+    pub fn unexplained_count(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.status != DeviationStatus::Accepted)
+            .count()
+    }
+    /// Returns a list of the deviations.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - input variables of the step (`None` for the whole extrinsic)
+    /// - baseline average time (`None` if new)
+    /// - current average time (`None` if missing)
+    /// - change (%)
+    /// - status
+    ///
+    /// # Example output:
+    /// ```ignore
+    /// vec![
+    ///     ("balances", "transfer", None, Some(187680.2), Some(211000.0), Some(12.4253), DeviationStatus::Accepted),
+    ///     ("balances", "transfer", Some(&[1]), Some(180000.0), Some(230000.0), Some(27.7778), DeviationStatus::Unexplained),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<BaselineCheckRow<'_>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                (
+                    e.pallet,
+                    e.extrinsic,
+                    e.input_vars,
+                    e.baseline_time,
+                    e.current_time,
+                    e.percentage,
+                    e.status,
+                )
+            })
+            .collect()
+    }
+    fn build_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![
+            bc =>
+            "Pallet",
+            "Extrinsic",
+            "Step",
+            "Baseline\nAvg. Time",
+            "Current\nAvg. Time",
+            "Change (%)",
+            "Status",
+            "Reason"
+        ]);
+
+        // Body
+        for entry in &self.entries {
+            table.add_row(row![
+                entry.pallet,
+                entry.extrinsic,
+                entry
+                    .input_vars
+                    .map(display_slice)
+                    .unwrap_or_else(|| String::from("-")),
+                display_option(entry.baseline_time),
+                display_option(entry.current_time),
+                display_option(entry.percentage),
+                entry.status,
+                entry.reason.unwrap_or_default(),
+            ]);
+        }
+
+        table
+    }
+    pub fn print(&self) {
+        self.build_table().printstd();
+    }
+    pub fn print_csv(&self) {
+        self.build_table().to_csv(stdout()).unwrap();
+    }
+}
+
+/// Whether an extrinsic was approved in the review ledger.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReviewStatus {
+    /// Approved, the result file did not change since.
+    Approved,
+    /// Approved, but the result file changed since.
+    Changed,
+    NeverReviewed,
+}
+
+impl fmt::Display for ReviewStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReviewStatus::Approved => write!(f, "approved"),
+            ReviewStatus::Changed => write!(f, "changed since approval"),
+            ReviewStatus::NeverReviewed => write!(f, "never reviewed"),
+        }
+    }
+}
+
+/// Row of the review table, see `ReviewTable::raw_list`.
+pub type ReviewRow<'a> = (
+    &'a str,
+    &'a str,
+    ReviewStatus,
+    Option<&'a str>,
+    Option<f64>,
+    f64,
+    Option<f64>,
+    Option<f64>,
+    f64,
+);
+
+#[derive(Debug, Default)]
+pub struct ReviewTable<'a> {
+    entries: Vec<ReviewTableEntry<'a>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct ReviewTableEntry<'a> {
+    pub pallet: &'a str,
+    pub extrinsic: &'a str,
+    pub status: ReviewStatus,
+    pub reviewer: Option<&'a str>,
+    /// Time of the approval, in seconds since the Unix epoch.
+    pub approved_at: Option<u64>,
+    /// Average extrinsic time at the time of the approval.
+    pub approved_time: Option<f64>,
+    pub current_time: f64,
+    pub percentage: Option<f64>,
+    /// Ratio to the fastest extrinsic at the time of the approval.
+    pub approved_ratio: Option<f64>,
+    pub current_ratio: f64,
+}
+
+impl<'a> ReviewTable<'a> {
+    pub fn new() -> Self {
+        ReviewTable {
+            entries: Vec::new(),
+        }
+    }
+    pub(crate) fn push(&mut self, entry: ReviewTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    pub fn sort_by_extrinsic(&mut self) {
+        self.entries
+            .sort_by(|a, b| (a.pallet, a.extrinsic).cmp(&(b.pallet, b.extrinsic)));
+    }
+    /// Amount of extrinsics with the given status.
+    pub fn count(&self, status: ReviewStatus) -> usize {
+        self.entries.iter().filter(|e| e.status == status).count()
+    }
+    /// Returns a list of the extrinsics and their review status.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - status
+    /// - reviewer (`None` if never reviewed)
+    /// - approved average extrinsic time
+    /// - current average extrinsic time
+    /// - change (%)
+    /// - approved ratio
+    /// - current ratio
+    ///
+    /// # Example output:
+    /// ```ignore
+    /// vec![
+    ///     ("balances", "transfer", ReviewStatus::Approved, Some("alice"), Some(187680.2), 187680.2, Some(0.0), Some(2.4502), 2.4502),
+    ///     ("identity", "add_registrar", ReviewStatus::NeverReviewed, None, None, 76600.8, None, None, 1.0),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<ReviewRow<'_>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                (
+                    e.pallet,
+                    e.extrinsic,
+                    e.status,
+                    e.reviewer,
+                    e.approved_time,
+                    e.current_time,
+                    e.percentage,
+                    e.approved_ratio,
+                    e.current_ratio,
+                )
+            })
+            .collect()
+    }
+    fn build_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![
+            bc =>
+            "Pallet",
+            "Extrinsic",
+            "Status",
+            "Reviewer",
+            "Approved",
+            "Approved\nAvg. Time",
+            "Current\nAvg. Time",
+            "Change (%)",
+            "Approved\nRatio (1:x)",
+            "Current\nRatio (1:x)"
+        ]);
+
+        // Body
+        for entry in &self.entries {
+            table.add_row(row![
+                entry.pallet,
+                entry.extrinsic,
+                entry.status,
+                entry.reviewer.unwrap_or("-"),
+                entry
+                    .approved_at
+                    .map(display_date)
+                    .unwrap_or_else(|| String::from("-")),
+                display_option(entry.approved_time),
+                entry.current_time,
+                display_option(entry.percentage),
+                display_option(entry.approved_ratio),
+                entry.current_ratio,
+            ]);
+        }
+
+        table
+    }
+    pub fn print(&self) {
+        self.build_table().printstd();
+    }
+    pub fn print_csv(&self) {
+        self.build_table().to_csv(stdout()).unwrap();
+    }
+}
+
+/// Row of the rank table, see `RankTable::raw_list`.
+pub type RankRow<'a> = (&'a str, &'a str, f64, f64, f64, f64, f64);
+
+#[derive(Debug, Default)]
+pub struct RankTable<'a> {
+    entries: Vec<RankTableEntry<'a>>,
+    /// Kendall's tau-b of the ranks, `None` if less than two extrinsics are ranked.
+    pub(crate) kendall_tau: Option<f64>,
+    /// Spearman's rank correlation, `None` if less than two extrinsics are ranked.
+    pub(crate) spearman: Option<f64>,
+}
+
+#[derive(Debug)]
+pub(crate) struct RankTableEntry<'a> {
+    pub pallet: &'a str,
+    pub extrinsic: &'a str,
+    /// Rank in the old run, starting at 1 for the fastest extrinsic.
+    pub old_rank: f64,
+    pub new_rank: f64,
+    /// Ratio to the fastest ranked extrinsic of the old run.
+    pub old_ratio: f64,
+    pub new_ratio: f64,
+}
+
+impl RankTableEntry<'_> {
+    /// Positive if the extrinsic became slower relative to the others.
+    fn movement(&self) -> f64 {
+        self.new_rank - self.old_rank
+    }
+}
+
+impl<'a> RankTable<'a> {
+    pub fn new() -> Self {
+        RankTable {
+            entries: Vec::new(),
+            kendall_tau: None,
+            spearman: None,
+        }
+    }
+    pub(crate) fn push(&mut self, entry: RankTableEntry<'a>) {
+        self.entries.push(entry);
+    }
+    pub fn kendall_tau(&self) -> Option<f64> {
+        self.kendall_tau
+    }
+    pub fn spearman(&self) -> Option<f64> {
+        self.spearman
+    }
+    /// Amount of ranked extrinsics, i.e. the ones which exist in both runs.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+    /// Sorts by the rank movement, the largest (in either direction) first.
+    pub fn sort_by_movement(&mut self) {
+        self.entries.sort_by(|a, b| {
+            b.movement()
+                .abs()
+                .partial_cmp(&a.movement().abs())
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| {
+                    a.old_rank
+                        .partial_cmp(&b.old_rank)
+                        .unwrap_or(Ordering::Equal)
+                })
+        });
+    }
+    /// Only keeps the `count` first extrinsics which changed their rank.
+    pub fn retain_moved(&mut self, count: usize) {
+        self.entries.retain(|e| e.movement() != 0.0);
+        self.entries.truncate(count);
+    }
+    /// Returns a list of the ranked extrinsics.
+    ///
+    /// Data ordered as:
+    /// - pallet
+    /// - extrinsic
+    /// - old rank
+    /// - new rank
+    /// - movement (positive if slower relative to the others)
+    /// - old ratio
+    /// - new ratio
+    ///
+    /// # Example output:
+    /// ```ignore
+    /// vec![
+    ///     ("treasury", "tip_new", 2.0, 3.0, 1.0, 1.4042, 1.873),
+    ///     ("balances", "transfer", 3.0, 2.0, -1.0, 1.9096, 1.7632),
+    /// ];
+    /// ```
+    pub fn raw_list(&self) -> Vec<RankRow<'_>> {
+        self.entries
+            .iter()
+            .map(|e| {
+                (
+                    e.pallet,
+                    e.extrinsic,
+                    e.old_rank,
+                    e.new_rank,
+                    e.movement(),
+                    e.old_ratio,
+                    e.new_ratio,
+                )
+            })
+            .collect()
+    }
+    fn build_table(&self) -> prettytable::Table {
+        let mut table = prettytable::Table::new();
+
+        // Header
+        table.add_row(row![
+            bc =>
+            "Pallet",
+            "Extrinsic",
+            "Old Rank",
+            "New Rank",
+            "Movement",
+            "Old Ratio\n(1:x)",
+            "New Ratio\n(1:x)"
+        ]);
+
+        // Body
+        for entry in self.raw_list() {
+            table.add_row(row![
+                entry.0, entry.1, entry.2, entry.3, entry.4, entry.5, entry.6
+            ]);
+        }
+
+        table
+    }
+    pub fn print(&self) {
+        self.build_table().printstd();
+    }
+    /// Prints the table, highlighting the rows which changed since the last time.
+    pub fn print_highlighted(&self, highlighter: &mut RowHighlighter) {
+        highlighter.print(self.build_table());
+    }
+    /// Prints the table, preceded by the rank correlations as `#` comment lines.
+    pub fn print_csv(&self) {
+        println!("# Kendall's tau: {}", display_option(self.kendall_tau));
+        println!("# Spearman's rho: {}", display_option(self.spearman));
+        self.build_table().to_csv(stdout()).unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_timestamp() {
+        assert_eq!(display_timestamp(0), "1970-01-01 00:00");
+        assert_eq!(display_timestamp(951_782_400), "2000-02-29 00:00");
+        assert_eq!(display_timestamp(1_614_607_500), "2021-03-01 14:05");
+    }
+}
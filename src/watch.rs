@@ -0,0 +1,129 @@
+//! Re-analysis of the result files whenever they change, see `--watch`.
+
+use crate::filescraper::{find_files, read_file};
+use crate::{ExtrinsicCollection, ExtrinsicResult};
+use failure::Error;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Keeps the parsed results of the files below a directory, re-parsing only the
+/// files which changed since the last poll.
+#[derive(Debug)]
+pub struct ResultWatcher {
+    root: PathBuf,
+    files: BTreeMap<PathBuf, WatchedFile>,
+}
+
+#[derive(Debug)]
+struct WatchedFile {
+    modified: Option<SystemTime>,
+    len: u64,
+    /// The parsed result, or the reason why the file is invalid.
+    result: Result<ExtrinsicResult, String>,
+}
+
+impl ResultWatcher {
+    pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        ResultWatcher {
+            root: root.as_ref().to_path_buf(),
+            files: BTreeMap::new(),
+        }
+    }
+    /// Searches the directory for added, modified and removed files and returns
+    /// how many changed. Files are considered modified if their modification
+    /// time or their size changed.
+    pub fn poll(&mut self) -> Result<usize, Error> {
+        let paths: HashSet<PathBuf> = find_files(&self.root)?.into_iter().collect();
+        let before = self.files.len();
+        self.files.retain(|path, _| paths.contains(path));
+        let mut changed = before - self.files.len();
+
+        for path in paths {
+            let metadata = match fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                // Removed since searching the directory, detected by the next poll
+                Err(_) => continue,
+            };
+            let (modified, len) = (metadata.modified().ok(), metadata.len());
+
+            if let Some(file) = self.files.get(&path) {
+                if file.modified == modified && file.len == len {
+                    continue;
+                }
+            }
+
+            let result = read_file(&path)
+                .and_then(|content| content.parse())
+                .map_err(|err| err.to_string());
+            self.files.insert(
+                path,
+                WatchedFile {
+                    modified,
+                    len,
+                    result,
+                },
+            );
+            changed += 1;
+        }
+
+        Ok(changed)
+    }
+    /// The results of all valid files, ordered by their path.
+    pub fn collection(&self) -> ExtrinsicCollection {
+        let mut collection = ExtrinsicCollection::new();
+        for file in self.files.values() {
+            if let Ok(result) = &file.result {
+                collection.push(result.clone());
+            }
+        }
+
+        collection
+    }
+    /// Reasons why files are invalid.
+    pub fn warnings(&self) -> Vec<&str> {
+        self.files
+            .values()
+            .filter_map(|file| file.result.as_ref().err())
+            .map(|err| err.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESULT: &str = "Pallet: \"balances\", Extrinsic: \"transfer\", Lowest values: [], Highest values: [], Steps: [1], Repeat: 2
+u,e,extrinsic_time,storage_root_time
+1,1,100,10
+1,1,120,12
+";
+
+    #[test]
+    fn test_poll() {
+        let dir = std::env::temp_dir().join(format!("bench-review-watch-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("balances_transfer.txt");
+
+        fs::write(&path, RESULT).unwrap();
+        fs::write(dir.join("invalid.txt"), "invalid").unwrap();
+        let mut watcher = ResultWatcher::new(&dir);
+        assert_eq!(watcher.poll().unwrap(), 2);
+        assert_eq!(watcher.poll().unwrap(), 0);
+        assert_eq!(watcher.collection().len(), 1);
+        assert_eq!(watcher.warnings().len(), 1);
+
+        fs::write(&path, format!("{}1,1,140,14\n", RESULT)).unwrap();
+        assert_eq!(watcher.poll().unwrap(), 1);
+        assert_eq!(watcher.collection().results[0].steps_repeats.len(), 3);
+
+        fs::remove_file(dir.join("invalid.txt")).unwrap();
+        assert_eq!(watcher.poll().unwrap(), 1);
+        assert!(watcher.warnings().is_empty());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,411 @@
+//! Parser for existing Rust weight files, e.g. the `weights.rs` of a pallet or
+//! the output of `generate-weights`.
+
+use std::fs;
+use std::path::Path;
+
+use failure::Error;
+use regex::Regex;
+
+#[allow(non_local_definitions)]
+mod error {
+    #[derive(Debug, Fail)]
+    pub(super) enum WeightFileError {
+        #[fail(display = "weight function \"{}\" is not closed", 0)]
+        UnclosedFunction(String),
+        #[fail(display = "the base weight of weight function \"{}\" is missing", 0)]
+        MissingBaseWeight(String),
+        #[fail(
+            display = "weight function \"{}\" contains an unsupported term: {}",
+            0, 1
+        )]
+        UnsupportedTerm(String, String),
+    }
+}
+
+use error::WeightFileError::*;
+
+/// A weight function, e.g. `fn transfer(u: u32, e: u32) -> Weight { ... }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightFunction {
+    /// Name of the enclosing module (e.g. `pub mod balances { ... }`), if any.
+    pub module: Option<String>,
+    pub name: String,
+    /// Parameter names as written, e.g. `u` or `_u` for unused parameters.
+    pub params: Vec<String>,
+    pub base_weight: u64,
+    pub component_weights: Vec<(String, u64)>,
+    /// Constant amount of database operations (reads + writes).
+    pub db_ops: u64,
+    /// Database operations (reads + writes) per unit of a component.
+    pub component_db_ops: Vec<(String, u64)>,
+}
+
+impl WeightFunction {
+    /// Parameter names without the leading underscore of unused parameters.
+    pub fn param_names(&self) -> Vec<&str> {
+        self.params
+            .iter()
+            .map(|param| param.trim_start_matches('_'))
+            .collect()
+    }
+    /// Evaluates the weight at the given component values, where database
+    /// operations are converted with the given weight per operation.
+    pub(crate) fn weight_at(&self, components: &[(&str, u64)], db_op_weight: f64) -> f64 {
+        let value = |name: &str| {
+            components
+                .iter()
+                .find(|(component, _)| *component == name)
+                .map(|(_, value)| *value as f64)
+                .unwrap_or(0.0)
+        };
+
+        let weight = self.base_weight as f64
+            + self
+                .component_weights
+                .iter()
+                .map(|(name, weight)| *weight as f64 * value(name))
+                .sum::<f64>();
+
+        let db_ops = self.db_ops as f64
+            + self
+                .component_db_ops
+                .iter()
+                .map(|(name, ops)| *ops as f64 * value(name))
+                .sum::<f64>();
+
+        weight + db_ops * db_op_weight
+    }
+}
+
+/// Finds the weight function of the given extrinsic. Functions within a module
+/// must be in a module named after the pallet, other functions are matched by the
+/// `pallet` of the weight file, if specified.
+pub(crate) fn find_weight_function<'a>(
+    functions: &'a [WeightFunction],
+    pallet: Option<&str>,
+    result_pallet: &str,
+    extrinsic: &str,
+) -> Option<&'a WeightFunction> {
+    functions.iter().find(|function| {
+        function.name == extrinsic
+            && match (&function.module, pallet) {
+                (Some(module), _) => module == result_pallet,
+                (None, Some(pallet)) => pallet == result_pallet,
+                (None, None) => true,
+            }
+    })
+}
+
+/// Reads and parses the weight file at the given path.
+pub fn read_weight_file<P: AsRef<Path>>(path: P) -> Result<Vec<WeightFunction>, Error> {
+    parse_weight_file(&fs::read_to_string(path)?)
+}
+
+/// Parses all weight functions of the given source code. Functions which occur
+/// multiple times with the same name and module (e.g. in `impl WeightInfo for
+/// SubstrateWeight<T>` and `impl WeightInfo for ()`) are only returned once.
+///
+/// Supported expressions:
+/// ```txt
+/// (65949000 as Weight)
+/// .saturating_add((u as Weight).saturating_mul(1000))
+/// Weight::from_ref_time(65_949_000 as u64)
+/// .saturating_add(Weight::from_ref_time(1_000 as u64).saturating_mul(u as u64))
+/// .saturating_add(Weight::from_ref_time(1_000).saturating_mul(u.into()))
+/// .saturating_add(T::DbWeight::get().reads(2 as Weight))
+/// .saturating_add(T::DbWeight::get().reads(2_u64))
+/// .saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(u as Weight)))
+/// .saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(u as u64)))
+/// ```
+///
+/// Any other `saturating_add` term is an error, rather than being skipped.
+pub fn parse_weight_file(source: &str) -> Result<Vec<WeightFunction>, Error> {
+    // Numbers like `1_000`, `1_000_u64` or `1_000 as Weight`
+    const NUMBER: &str = r"([\d_]+)(?:u64)?(?:\s+as\s+(?:Weight|u64))?";
+    // Components like `u as Weight`, `u as u64` or `u.into()`
+    const COMPONENT: &str = r"([A-Za-z_]\w*)(?:\s+as\s+(?:Weight|u64)|\.into\(\))";
+
+    // Unwrapping is ok, since the expressions are valid.
+    let regex = |pattern: String| Regex::new(&pattern).unwrap();
+    let module_regex = Regex::new(r"\bmod\s+(\w+)\s*\{").unwrap();
+    let function_regex = Regex::new(r"\bfn\s+(\w+)\s*\(([^)]*)\)\s*->\s*Weight\s*\{").unwrap();
+    let base_regex = regex(format!(
+        r"^\s*\(\s*{NUMBER}\s*\)|^\s*Weight::from_ref_time\(\s*{NUMBER}\s*\)"
+    ));
+    let component_regex = regex(format!(
+        r"^\s*\(\s*{COMPONENT}\s*\)\s*\.saturating_mul\(\s*{NUMBER}\s*\)\s*$"
+    ));
+    let ref_time_component_regex = regex(format!(
+        r"^\s*Weight::from_ref_time\(\s*{NUMBER}\s*\)\s*\.saturating_mul\(\s*{COMPONENT}\s*\)\s*$"
+    ));
+    let db_regex = regex(format!(r"\.(?:reads|writes)\(\s*{NUMBER}\s*\)\s*$"));
+    let component_db_regex = regex(format!(
+        r"\.(?:reads|writes)\(\s*\(\s*{NUMBER}\s*\)\s*\.saturating_mul\(\s*{COMPONENT}\s*\)\s*\)\s*$"
+    ));
+    let term_regex = Regex::new(r"\.saturating_add\(").unwrap();
+
+    // Signature: (module name, start, end)
+    let mut modules = Vec::new();
+    for captures in module_regex.captures_iter(source) {
+        // Unwrapping is ok, since the full match and the group always exist.
+        let full = captures.get(0).unwrap();
+        let end = find_closing(source, full.end(), '{', '}')
+            .ok_or_else(|| UnclosedFunction(captures[1].to_string()))?;
+        modules.push((captures[1].to_string(), full.start(), end));
+    }
+
+    let mut functions: Vec<WeightFunction> = Vec::new();
+    for captures in function_regex.captures_iter(source) {
+        let full = captures.get(0).unwrap();
+        let name = captures[1].to_string();
+
+        let end = find_closing(source, full.end(), '{', '}')
+            .ok_or_else(|| UnclosedFunction(name.clone()))?;
+        // Without comments, e.g. the `// Standard Error: ...` of generated files
+        let body: String = source[full.end()..end]
+            .lines()
+            .map(|line| line.split("//").next().unwrap_or_default())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let body = body.as_str();
+
+        // Innermost module which contains the function.
+        let module = modules
+            .iter()
+            .filter(|(_, start, end)| *start < full.start() && full.start() < *end)
+            .max_by_key(|(_, start, _)| *start)
+            .map(|(module, _, _)| module.clone());
+
+        let params = captures[2]
+            .split(',')
+            .filter_map(|param| param.split(':').next())
+            .map(|param| param.trim().to_string())
+            .filter(|param| !param.is_empty())
+            .collect();
+
+        let base_captures = base_regex
+            .captures(body)
+            .ok_or_else(|| MissingBaseWeight(name.clone()))?;
+        let base_weight = base_captures
+            .get(1)
+            .or_else(|| base_captures.get(2))
+            .map(|number| parse_number(number.as_str()))
+            .transpose()?
+            .unwrap_or(0);
+
+        let mut component_weights: Vec<(String, u64)> = Vec::new();
+        let mut db_ops = 0;
+        let mut component_db_ops: Vec<(String, u64)> = Vec::new();
+
+        // Each `saturating_add` adds one term to the base weight.
+        for term_match in term_regex.find_iter(body) {
+            let term_end = find_closing(body, term_match.end(), '(', ')')
+                .ok_or_else(|| UnclosedFunction(name.clone()))?;
+            let term = &body[term_match.end()..term_end];
+
+            if let Some(c) = component_regex.captures(term) {
+                component_weights.push((c[1].to_string(), parse_number(&c[2])?));
+            } else if let Some(c) = ref_time_component_regex.captures(term) {
+                component_weights.push((c[2].to_string(), parse_number(&c[1])?));
+            } else if let Some(c) = db_regex.captures(term) {
+                db_ops += parse_number(&c[1])?;
+            } else if let Some(c) = component_db_regex.captures(term) {
+                let ops = parse_number(&c[1])?;
+                match component_db_ops.iter_mut().find(|(name, _)| name == &c[2]) {
+                    Some((_, total)) => *total += ops,
+                    None => component_db_ops.push((c[2].to_string(), ops)),
+                }
+            } else {
+                let term = term.split_whitespace().collect::<Vec<_>>().join(" ");
+                return Err(UnsupportedTerm(name, term).into());
+            }
+        }
+
+        let function = WeightFunction {
+            module,
+            name,
+            params,
+            base_weight,
+            component_weights,
+            db_ops,
+            component_db_ops,
+        };
+
+        if !functions
+            .iter()
+            .any(|f| f.name == function.name && f.module == function.module)
+        {
+            functions.push(function);
+        }
+    }
+
+    Ok(functions)
+}
+
+/// Returns the position of the brace (or parenthesis) which closes the block
+/// starting at `start` (right after the opening one).
+fn find_closing(source: &str, start: usize, open: char, close: char) -> Option<usize> {
+    let mut depth = 1;
+    for (index, c) in source[start..].char_indices() {
+        if c == open {
+            depth += 1;
+        } else if c == close {
+            depth -= 1;
+            if depth == 0 {
+                return Some(start + index);
+            }
+        }
+    }
+
+    None
+}
+
+/// Parses numbers like `65_949_000`.
+fn parse_number(number: &str) -> Result<u64, Error> {
+    Ok(number.replace('_', "").parse::<u64>()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WEIGHT_FILE: &str = r#"
+use frame_support::weights::{Weight, constants::RocksDbWeight};
+
+pub trait WeightInfo {
+    fn transfer(u: u32, e: u32) -> Weight;
+    fn remark(_b: u32) -> Weight;
+}
+
+pub struct SubstrateWeight<T>(PhantomData<T>);
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    fn transfer(u: u32, e: u32, ) -> Weight {
+        (65_949_000 as Weight)
+            .saturating_add((u as Weight).saturating_mul(1_000 as Weight))
+            .saturating_add((e as Weight).saturating_mul(250))
+            .saturating_add(T::DbWeight::get().reads(2 as Weight))
+            .saturating_add(T::DbWeight::get().reads((1 as Weight).saturating_mul(u as Weight)))
+            .saturating_add(T::DbWeight::get().writes(1 as Weight))
+            .saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(u as Weight)))
+    }
+    fn remark(_b: u32) -> Weight {
+        (1_000 as Weight)
+    }
+}
+
+impl WeightInfo for () {
+    fn transfer(u: u32, e: u32, ) -> Weight {
+        (65_949_000 as Weight)
+    }
+    fn remark(_b: u32) -> Weight {
+        (1_000 as Weight)
+    }
+}
+
+pub mod democracy {
+    pub fn propose(p: u32) -> Weight {
+        Weight::from_ref_time(125_000_000)
+            .saturating_add((p as Weight).saturating_mul(500_000))
+    }
+}
+"#;
+
+    #[test]
+    fn test_parse_weight_file() {
+        let functions = parse_weight_file(WEIGHT_FILE).unwrap();
+        assert_eq!(functions.len(), 3);
+
+        assert_eq!(
+            functions[0],
+            WeightFunction {
+                module: None,
+                name: "transfer".to_string(),
+                params: vec!["u".to_string(), "e".to_string()],
+                base_weight: 65_949_000,
+                component_weights: vec![("u".to_string(), 1_000), ("e".to_string(), 250)],
+                db_ops: 3,
+                component_db_ops: vec![("u".to_string(), 3)],
+            }
+        );
+
+        assert_eq!(functions[1].name, "remark");
+        assert_eq!(functions[1].params, vec!["_b"]);
+        assert_eq!(functions[1].base_weight, 1_000);
+        assert!(functions[1].component_weights.is_empty());
+
+        assert_eq!(functions[2].module, Some("democracy".to_string()));
+        assert_eq!(functions[2].name, "propose");
+        assert_eq!(functions[2].base_weight, 125_000_000);
+        assert_eq!(
+            functions[2].component_weights,
+            vec![("p".to_string(), 500_000)]
+        );
+    }
+
+    const REF_TIME_WEIGHT_FILE: &str = r#"
+impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
+    fn transfer(u: u32, ) -> Weight {
+        // Minimum execution time: 65_000 nanoseconds.
+        Weight::from_ref_time(65_949_000 as u64)
+            // Standard Error: 1_000
+            .saturating_add(Weight::from_ref_time(1_000 as u64).saturating_mul(u as u64))
+            .saturating_add(T::DbWeight::get().reads(2 as u64))
+            .saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(u as u64)))
+            .saturating_add(T::DbWeight::get().writes(1_u64))
+    }
+    fn remark(b: u32, ) -> Weight {
+        Weight::from_ref_time(1_000)
+            .saturating_add(Weight::from_ref_time(250).saturating_mul(b.into()))
+            .saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(b.into())))
+    }
+}
+"#;
+
+    #[test]
+    fn test_parse_ref_time_weight_file() {
+        let functions = parse_weight_file(REF_TIME_WEIGHT_FILE).unwrap();
+        assert_eq!(functions.len(), 2);
+
+        assert_eq!(
+            functions[0],
+            WeightFunction {
+                module: None,
+                name: "transfer".to_string(),
+                params: vec!["u".to_string()],
+                base_weight: 65_949_000,
+                component_weights: vec![("u".to_string(), 1_000)],
+                db_ops: 3,
+                component_db_ops: vec![("u".to_string(), 1)],
+            }
+        );
+
+        assert_eq!(functions[1].base_weight, 1_000);
+        assert_eq!(functions[1].component_weights, vec![("b".to_string(), 250)]);
+        assert_eq!(functions[1].component_db_ops, vec![("b".to_string(), 2)]);
+    }
+
+    #[test]
+    fn test_unsupported_term() {
+        let source = r#"
+fn transfer(u: u32, ) -> Weight {
+    Weight::from_ref_time(65_949_000)
+        .saturating_add(Weight::from_proof_size(2_603).saturating_mul(u.into()))
+}
+"#;
+        let error = parse_weight_file(source).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "weight function \"transfer\" contains an unsupported term: \
+             Weight::from_proof_size(2_603).saturating_mul(u.into())"
+        );
+    }
+
+    #[test]
+    fn test_weight_at() {
+        let functions = parse_weight_file(WEIGHT_FILE).unwrap();
+        let weight = functions[0].weight_at(&[("u", 10), ("e", 4)], 100.0);
+        // 65_949_000 + 10 * 1_000 + 4 * 250 + (3 + 10 * 3) * 100
+        assert_eq!(weight, 65_963_300.0);
+    }
+}
@@ -0,0 +1,64 @@
+//! Conversion of the measured times into `Weight` values, as used by the runtime.
+
+/// Parameters for converting the measured times into weights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightConfig {
+    /// Weight per nanosecond of extrinsic time. Substrate uses `1_000`
+    /// (`WEIGHT_PER_NANOS`), i.e. one unit of weight equals one picosecond.
+    pub weight_per_nanos: f64,
+    /// Multiplier which is applied to the measured times as a safety margin,
+    /// e.g. `1.2` for a margin of 20%.
+    pub safety_multiplier: f64,
+    /// Weight of a single database operation (read or write). Database operations
+    /// are only available in newer result files and are not accounted for by default.
+    pub db_op_weight: f64,
+}
+
+impl Default for WeightConfig {
+    fn default() -> Self {
+        WeightConfig {
+            weight_per_nanos: 1_000.0,
+            safety_multiplier: 1.0,
+            db_op_weight: 0.0,
+        }
+    }
+}
+
+impl WeightConfig {
+    /// Converts the extrinsic time (in nanoseconds) and the amount of database
+    /// operations into weight. The safety multiplier only applies to the time.
+    pub(crate) fn weight(&self, extrinsic_time: f64, db_ops: f64) -> f64 {
+        extrinsic_time * self.weight_per_nanos * self.safety_multiplier + db_ops * self.db_op_weight
+    }
+}
+
+/// Converts a fitted coefficient into a weight. Negative coefficients, which can
+/// occur because of noise, are treated as zero.
+pub(crate) fn to_weight(coefficient: f64) -> u64 {
+    coefficient.max(0.0).ceil() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weight() {
+        let config = WeightConfig::default();
+        assert_eq!(config.weight(100.0, 5.0), 100_000.0);
+
+        let config = WeightConfig {
+            weight_per_nanos: 1_000.0,
+            safety_multiplier: 1.5,
+            db_op_weight: 25.0,
+        };
+        assert_eq!(config.weight(100.0, 4.0), 150_100.0);
+    }
+
+    #[test]
+    fn test_to_weight() {
+        assert_eq!(to_weight(10.2), 11);
+        assert_eq!(to_weight(10.0), 10);
+        assert_eq!(to_weight(-3.0), 0);
+    }
+}
@@ -0,0 +1,88 @@
+extern crate libreview;
+
+use libreview::tables::DeviationStatus;
+use libreview::{BaselineConfig, BaselineSnapshot, Metric};
+
+use failure::Error;
+
+mod common;
+use common::collection;
+
+#[test]
+fn test_baseline_round_trip() -> Result<(), Error> {
+    let path = std::env::temp_dir().join(format!("bench-baseline-{}.json", std::process::id()));
+
+    let full = collection("tests/files/full/")?;
+    BaselineSnapshot::new(&full, Metric::ExtrinsicTime, None)?.write(&path)?;
+    let snapshot = BaselineSnapshot::read(&path)?;
+    std::fs::remove_file(&path)?;
+
+    let config = BaselineConfig::default();
+    let table = snapshot.check(&full, &config, "2021-01-01");
+    assert!(table.is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn test_baseline_check() -> Result<(), Error> {
+    let shortened = collection("tests/files/shortened/")?;
+    let full = collection("tests/files/full/")?;
+
+    let snapshot = BaselineSnapshot::new(&shortened, Metric::ExtrinsicTime, None)?;
+    let config = BaselineConfig::read("tests/files/baseline/baseline.toml")?;
+    let mut table = snapshot.check(&full, &config, "2021-01-01");
+    table.sort_by_extrinsic();
+
+    let extrinsics: Vec<_> = table
+        .raw_list()
+        .into_iter()
+        .filter(|row| row.2.is_none())
+        .collect();
+
+    assert_eq!(
+        extrinsics,
+        vec![
+            (
+                "identity",
+                "add_registrar",
+                None,
+                Some(76600.8),
+                Some(82669.6368),
+                Some(7.9227),
+                DeviationStatus::Accepted
+            ),
+            (
+                "treasury",
+                "tip_new",
+                None,
+                Some(140659.8333),
+                Some(176963.0696),
+                Some(25.8092),
+                DeviationStatus::Expired
+            ),
+        ]
+    );
+
+    // The step which is also part of the baseline deviates by more than the tolerance
+    let step = table
+        .raw_list()
+        .into_iter()
+        .find(|row| row.1 == "transfer" && row.3.is_some())
+        .unwrap();
+    assert_eq!(step.2, Some(&[199, 1000][..]));
+    assert_eq!(step.6, DeviationStatus::Unexplained);
+
+    // Only the steps of the accepted extrinsic are explained by the allowlist
+    for row in table.raw_list() {
+        assert_eq!(
+            row.6 == DeviationStatus::Accepted,
+            row.0 == "identity",
+            "{:?}",
+            row
+        );
+    }
+    assert_eq!(table.unexplained_count(), 64);
+
+    Ok(())
+}
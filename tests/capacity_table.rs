@@ -0,0 +1,43 @@
+extern crate libreview;
+
+use libreview::{CapacityConfig, ExtrinsicCollection, FileScraper, Metric};
+
+use failure::Error;
+
+#[test]
+#[rustfmt::skip]
+fn test_capacity_table() -> Result<(), Error> {
+    let scraper = FileScraper::new("tests/files/full/")?;
+    let mut collection = ExtrinsicCollection::new();
+
+    for result in scraper {
+        let extrinsic_result = result?.parse()?;
+        collection.push(extrinsic_result);
+    }
+
+    // 2s blocks, 75% for normal dispatches
+    let mut table = collection
+        .generate_capacity_table(&CapacityConfig::default(), Metric::ExtrinsicTime)
+        .unwrap();
+    table.sort_by_worst_case();
+
+    let expected = vec![
+        ("democracy", "delegate", 1514997.45, 990, 2880319.0, 520, 2835334.1, 529),
+        ("treasury", "tip_new", 176963.0696, 8476, 246087.0, 6095, 196989.2, 7614),
+        ("balances", "transfer", 184602.4227, 8125, 234044.0, 6409, 185658.6, 8079),
+        ("staking", "bond_extra", 186282.0091, 8052, 200732.0, 7472, 183681.2, 8166),
+        ("identity", "add_registrar", 82669.6368, 18144, 96384.0, 15562, 90736.8, 16531),
+    ];
+
+    assert_eq!(table.raw_list(), expected);
+
+    // Same limit expressed as a block weight
+    let config = CapacityConfig::from_block_weight(2_000_000_000_000.0, 1_000.0, 0.75);
+    let table = collection.generate_capacity_table(&config, Metric::ExtrinsicTime).unwrap();
+    let mut calls: Vec<u64> = table.raw_list().iter().map(|row| row.7).collect();
+    calls.sort_unstable();
+
+    assert_eq!(calls, vec![529, 7614, 8079, 8166, 16531]);
+
+    Ok(())
+}
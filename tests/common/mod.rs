@@ -0,0 +1,24 @@
+//! Helpers shared by the integration tests.
+
+// Not every test uses every helper.
+#![allow(dead_code)]
+
+use failure::Error;
+use libreview::{ExtrinsicCollection, FileScraper};
+
+/// Parses all result files of the scraper into a collection.
+pub fn scrape(scraper: FileScraper) -> Result<ExtrinsicCollection, Error> {
+    let mut collection = ExtrinsicCollection::new();
+
+    for result in scraper {
+        let extrinsic_result = result?.parse()?;
+        collection.push(extrinsic_result);
+    }
+
+    Ok(collection)
+}
+
+/// Parses all result files within the path into a collection.
+pub fn collection(path: &str) -> Result<ExtrinsicCollection, Error> {
+    scrape(FileScraper::new(path)?)
+}
@@ -0,0 +1,61 @@
+extern crate libreview;
+
+use libreview::Metric;
+
+use failure::Error;
+
+mod common;
+use common::collection;
+
+#[test]
+#[rustfmt::skip]
+/// The base cost stays the same, but the marginal cost of `u` doubles.
+fn test_compare_steps() -> Result<(), Error> {
+    let old = collection("tests/files/compare/old/")?;
+    let new = collection("tests/files/compare/new/")?;
+
+    let mut table = old.compare_steps(&new, Metric::ExtrinsicTime).unwrap();
+    table.sort_by_extrinsic();
+
+    let expected: Vec<(&str, &str, &[u64], _, _, _)> = vec![
+        ("balances", "transfer", &[1], Some(1010.0), Some(1020.0), Some(0.9901)),
+        ("balances", "transfer", &[10], Some(1100.0), Some(1200.0), Some(9.0909)),
+        ("balances", "transfer", &[20], Some(1200.0), Some(1400.0), Some(16.6667)),
+        ("balances", "transfer", &[30], None, Some(1600.0), None),
+    ];
+
+    assert_eq!(table.raw_list(), expected);
+
+    let expected = vec![
+        ("balances", "transfer", "base", 1000.0, 1000.0, Some(0.0)),
+        ("balances", "transfer", "u", 10.0, 20.0, Some(100.0)),
+    ];
+
+    assert_eq!(table.raw_costs(), expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_compare_steps_significance() -> Result<(), Error> {
+    let old = collection("tests/files/compare/old/")?;
+    let new = collection("tests/files/compare/new/")?;
+
+    let mut table = old.compare_steps(&new, Metric::ExtrinsicTime).unwrap();
+    table.sort_by_extrinsic();
+
+    // The repeats of the steps with `u` > 1 do not vary, so their change is
+    // certain.
+    let significance = table.raw_significance();
+    assert_eq!(significance[1].t_test_p, Some(0.0));
+    assert_eq!(significance[1].cliffs_delta, Some(1.0));
+    assert!(significance[1].is_significant(0.05));
+    assert!(!significance[0].is_significant(0.05));
+
+    // The step with `u` = 1 is dropped, the added step is kept.
+    table.retain_significant(0.05);
+    let steps: Vec<&[u64]> = table.raw_list().iter().map(|step| step.2).collect();
+    assert_eq!(steps, vec![&[10][..], &[20], &[30]]);
+
+    Ok(())
+}
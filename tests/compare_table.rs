@@ -0,0 +1,121 @@
+extern crate libreview;
+
+use libreview::tables::Significance;
+use libreview::{Metric, Normalization};
+
+use failure::Error;
+
+mod common;
+use common::collection;
+
+#[test]
+#[rustfmt::skip]
+fn test_compare() -> Result<(), Error> {
+    let old = collection("tests/files/shortened/")?;
+    let new = collection("tests/files/full/")?;
+
+    let mut table = old.compare(&new, Metric::ExtrinsicTime).unwrap();
+    table.sort_by_change();
+
+    let expected = vec![
+        ("treasury", "tip_new", Some(140659.8333), Some(176963.0696), Some(36303.2362), Some(25.8092)),
+        ("identity", "add_registrar", Some(76600.8), Some(82669.6368), Some(6068.8368), Some(7.9227)),
+        ("democracy", "delegate", Some(1501419.6), Some(1514997.45), Some(13577.85), Some(0.9043)),
+        ("staking", "bond_extra", Some(188244.0), Some(186282.0091), Some(-1961.9909), Some(-1.0423)),
+        ("balances", "transfer", Some(187680.2), Some(184602.4227), Some(-3077.7773), Some(-1.6399)),
+    ];
+
+    assert_eq!(table.raw_list(), expected);
+
+    Ok(())
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_compare_added_removed() -> Result<(), Error> {
+    let old = collection("tests/files/shortened/")?;
+    let new = collection("tests/files/steps/")?;
+
+    let mut table = old.compare(&new, Metric::ExtrinsicTime).unwrap();
+    table.sort_by_change();
+
+    let expected = vec![
+        ("balances", "set_balance_killing", None, Some(114227.0), None, None),
+        ("democracy", "propose", None, Some(130332.2), None, None),
+        ("balances", "transfer", Some(187680.2), None, None, None),
+        ("democracy", "delegate", Some(1501419.6), None, None, None),
+        ("identity", "add_registrar", Some(76600.8), None, None, None),
+        ("staking", "bond_extra", Some(188244.0), None, None, None),
+        ("treasury", "tip_new", Some(140659.8333), None, None, None),
+    ];
+
+    assert_eq!(table.raw_list(), expected);
+
+    Ok(())
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_compare_significance() -> Result<(), Error> {
+    let old = collection("tests/files/shortened/")?;
+    let new = collection("tests/files/full/")?;
+
+    let mut table = old.compare(&new, Metric::ExtrinsicTime).unwrap();
+    table.sort_by_change();
+
+    let significance = table.raw_significance();
+    assert_eq!(
+        significance[0],
+        Significance {
+            t_test_p: Some(0.0),
+            mann_whitney_p: Some(0.0),
+            cohens_d: Some(3.0991),
+            cliffs_delta: Some(1.0),
+        }
+    );
+    // Only one of the tests considers `identity.add_registrar` significant.
+    assert_eq!(significance[1].t_test_p, Some(0.192));
+    assert_eq!(significance[1].mann_whitney_p, Some(0.0197));
+
+    let significant: Vec<bool> = significance.iter().map(|s| s.is_significant(0.05)).collect();
+    assert_eq!(significant, vec![true, false, false, false, false]);
+
+    table.retain_significant(0.05);
+    let list = table.raw_list();
+    assert_eq!(list.len(), 1);
+    assert_eq!((list[0].0, list[0].1), ("treasury", "tip_new"));
+
+    Ok(())
+}
+
+#[test]
+fn test_compare_normalized() -> Result<(), Error> {
+    let mut old = collection("tests/files/shortened/")?;
+    let mut new = collection("tests/files/full/")?;
+
+    // The change of `balances.transfer` is attributed to the hardware
+    let normalization = Normalization::ReferenceSet(vec!["balances.transfer".parse()?]);
+    let factor = normalization.apply(&mut old, &mut new, Metric::ExtrinsicTime)?;
+    assert_eq!(factor, 0.9836);
+
+    let mut table = old.compare(&new, Metric::ExtrinsicTime)?;
+    table.sort_by_change();
+
+    let changes: Vec<_> = table
+        .raw_list()
+        .into_iter()
+        .map(|(pallet, extrinsic, .., percentage)| (pallet, extrinsic, percentage))
+        .collect();
+
+    let expected = vec![
+        ("treasury", "tip_new", Some(27.9068)),
+        ("identity", "add_registrar", Some(9.7221)),
+        ("democracy", "delegate", Some(2.5867)),
+        ("staking", "bond_extra", Some(0.6076)),
+        ("balances", "transfer", Some(0.0)),
+    ];
+
+    assert_eq!(changes, expected);
+
+    Ok(())
+}
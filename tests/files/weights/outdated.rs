@@ -0,0 +1,40 @@
+//! Weights which were not updated after the benchmarks changed.
+
+use frame_support::weights::Weight;
+
+pub mod balances {
+    use super::*;
+
+    pub fn transfer(_u: u32, x: u32) -> Weight {
+        (150000000 as Weight)
+            .saturating_add((x as Weight).saturating_mul(1000 as Weight))
+    }
+}
+
+pub mod democracy {
+    use super::*;
+
+    pub fn delegate() -> Weight {
+        (221000000 as Weight)
+    }
+}
+
+pub mod identity {
+    use super::*;
+
+    pub fn add_registrar(r: u32, s: u32) -> Weight {
+        (90000000 as Weight)
+            .saturating_add((r as Weight).saturating_mul(900000 as Weight))
+            .saturating_add((s as Weight).saturating_mul(1000 as Weight))
+    }
+}
+
+pub mod treasury {
+    use super::*;
+
+    pub fn tip_new(r: u32, t: u32) -> Weight {
+        (94000000 as Weight)
+            .saturating_add((r as Weight).saturating_mul(4000 as Weight))
+            .saturating_add((t as Weight).saturating_mul(400000 as Weight))
+    }
+}
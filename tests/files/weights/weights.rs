@@ -0,0 +1,42 @@
+//! Weights for the benchmarked pallets.
+
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::weights::{constants::RocksDbWeight as DbWeight, Weight};
+
+pub mod balances {
+    use super::*;
+
+    pub fn transfer(_u: u32, _e: u32) -> Weight {
+        (150000000 as Weight)
+    }
+}
+
+pub mod democracy {
+    use super::*;
+
+    pub fn delegate(r: u32) -> Weight {
+        (221000000 as Weight)
+            .saturating_add((r as Weight).saturating_mul(25000000 as Weight))
+    }
+}
+
+pub mod identity {
+    use super::*;
+
+    pub fn add_registrar(r: u32) -> Weight {
+        (90000000 as Weight)
+            .saturating_add((r as Weight).saturating_mul(900000 as Weight))
+    }
+}
+
+pub mod treasury {
+    use super::*;
+
+    pub fn tip_new(r: u32, t: u32) -> Weight {
+        (94000000 as Weight)
+            .saturating_add((r as Weight).saturating_mul(4000 as Weight))
+            .saturating_add((t as Weight).saturating_mul(400000 as Weight))
+    }
+}
@@ -0,0 +1,79 @@
+extern crate libreview;
+
+use libreview::{FileScraper, GitSource, Metric};
+
+use failure::Error;
+use std::path::Path;
+use std::process::Command;
+
+mod common;
+use common::{collection, scrape};
+
+fn git(repo: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .current_dir(repo)
+        .args([
+            "-c",
+            "user.name=bench",
+            "-c",
+            "user.email=bench@localhost",
+            "-c",
+            "commit.gpgsign=false",
+        ])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?} failed", args);
+    String::from_utf8(output.stdout).unwrap().trim().to_string()
+}
+
+/// Commits the files of `from` as `results` into the repository.
+fn commit_results(repo: &Path, from: &str) -> String {
+    let results = repo.join("results");
+    let _ = std::fs::remove_dir_all(&results);
+    std::fs::create_dir_all(&results).unwrap();
+    for entry in std::fs::read_dir(from).unwrap() {
+        let path = entry.unwrap().path();
+        std::fs::copy(&path, results.join(path.file_name().unwrap())).unwrap();
+    }
+
+    git(repo, &["add", "-A"]);
+    git(repo, &["commit", "-q", "-m", from]);
+    git(repo, &["rev-parse", "HEAD"])
+}
+
+#[test]
+fn test_git_source() -> Result<(), Error> {
+    let repo = std::env::temp_dir().join(format!("bench-review-git-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&repo);
+    std::fs::create_dir_all(&repo)?;
+    git(&repo, &["init", "-q"]);
+
+    let old_commit = commit_results(&repo, "tests/files/shortened");
+    let new_commit = commit_results(&repo, "tests/files/full");
+    // Changes of the working tree are not visible to git sources
+    std::fs::remove_dir_all(repo.join("results"))?;
+
+    let scraper = FileScraper::from_git(GitSource::new(&repo, "HEAD~1", "results"))?;
+    assert_eq!(scraper.commit(), Some(old_commit.as_str()));
+    let old = scrape(scraper)?;
+
+    let scraper = FileScraper::from_git(GitSource::new(&repo, "HEAD", "results"))?;
+    assert_eq!(scraper.commit(), Some(new_commit.as_str()));
+    let new = scrape(scraper)?;
+
+    // Same results as read from the directories
+    let old_dir = collection("tests/files/shortened")?;
+    let new_dir = collection("tests/files/full")?;
+    let mut table = old.compare(&new, Metric::ExtrinsicTime)?;
+    let mut table_dir = old_dir.compare(&new_dir, Metric::ExtrinsicTime)?;
+    table.sort_by_change();
+    table_dir.sort_by_change();
+    assert_eq!(table.raw_list(), table_dir.raw_list());
+
+    assert!(FileScraper::from_git(GitSource::new(&repo, "HEAD", "missing")).is_err());
+    assert!(FileScraper::from_git(GitSource::new(&repo, "unknown", "results")).is_err());
+
+    std::fs::remove_dir_all(&repo)?;
+    Ok(())
+}
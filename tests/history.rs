@@ -0,0 +1,153 @@
+extern crate libreview;
+
+use libreview::history::{self, DetectionConfig, HistoryStore};
+use libreview::tables::ChangeKind;
+use libreview::{Baseline, ExtrinsicId, Metric, RunMetadata};
+
+use failure::Error;
+
+mod common;
+use common::collection;
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("bench-review-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn test_history_round_trip() -> Result<(), Error> {
+    let dir = temp_dir("round-trip");
+
+    let shortened = collection("tests/files/shortened/")?;
+    let full = collection("tests/files/full/")?;
+
+    let store = HistoryStore::open(&dir)?;
+    let mut metadata = RunMetadata::new("a1b2c3");
+    metadata.commit = Some("a1b2c3d4e5f6".to_string());
+    metadata.machine = Some("bench-01".to_string());
+    metadata.runtime = Some("polkadot-9050".to_string());
+    store.record(&metadata, &shortened)?;
+    store.record(&RunMetadata::new("d4e5f6"), &full)?;
+
+    let runs = store.runs()?;
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0].metadata, metadata);
+    assert_eq!(runs[1].metadata.label, "d4e5f6");
+
+    // The loaded runs produce the same results as the original files
+    for (run, original) in runs.iter().zip(&[shortened, full]) {
+        assert_eq!(
+            run.collection
+                .generate_ratio_table(&Baseline::default())?
+                .raw_list(),
+            original
+                .generate_ratio_table(&Baseline::default())?
+                .raw_list()
+        );
+    }
+
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_history_trend() -> Result<(), Error> {
+    let dir = temp_dir("trend");
+    let store = HistoryStore::open(&dir)?;
+
+    for (label, timestamp, path) in &[
+        ("a1b2c3", 1614607500, "tests/files/shortened/"),
+        ("d4e5f6", 1614693900, "tests/files/full/"),
+        ("g7h8i9", 1614780300, "tests/files/shortened/"),
+    ] {
+        let mut metadata = RunMetadata::new(label);
+        metadata.timestamp = *timestamp;
+        store.record(&metadata, &collection(path)?)?;
+    }
+
+    let runs = store.runs()?;
+    let id: ExtrinsicId = "balances.transfer".parse()?;
+    let table = history::trend(&runs, &id, Metric::ExtrinsicTime)?;
+
+    let expected = vec![
+        ("a1b2c3", 1614607500, 187680.2, 180597.0, 14930.2564, None, None),
+        ("d4e5f6", 1614693900, 184602.4227, 182047.0, 10317.6132, Some(-1.6399), Some(-1.6399)),
+        ("g7h8i9", 1614780300, 187680.2, 180597.0, 14930.2564, Some(1.6672), Some(0.0)),
+    ];
+
+    assert_eq!(table.raw_list(), expected);
+
+    let id: ExtrinsicId = "balances.unknown".parse()?;
+    assert!(history::trend(&runs, &id, Metric::ExtrinsicTime).is_err());
+
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_history_speed_factor() -> Result<(), Error> {
+    let dir = temp_dir("speed-factor");
+    let store = HistoryStore::open(&dir)?;
+
+    store.record(
+        &RunMetadata::new("fast"),
+        &collection("tests/files/shortened/")?,
+    )?;
+    let mut metadata = RunMetadata::new("slow");
+    metadata.speed_factor = Some(2.0);
+    store.record(&metadata, &collection("tests/files/shortened/")?)?;
+
+    let mut runs = store.runs()?;
+    assert_eq!(runs[1].metadata.speed_factor, Some(2.0));
+    history::normalize(&mut runs, &[], Metric::ExtrinsicTime)?;
+
+    let id: ExtrinsicId = "balances.transfer".parse()?;
+    let table = history::trend(&runs, &id, Metric::ExtrinsicTime)?;
+    let averages: Vec<f64> = table.raw_list().into_iter().map(|row| row.2).collect();
+    assert_eq!(averages, vec![187680.2, 93840.2]);
+
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
+
+#[test]
+fn test_detect_changes() -> Result<(), Error> {
+    let dir = temp_dir("detect-changes");
+    let store = HistoryStore::open(&dir)?;
+
+    for (label, path) in &[
+        ("r1", "tests/files/shortened/"),
+        ("r2", "tests/files/shortened/"),
+        ("r3", "tests/files/shortened/"),
+        ("r4", "tests/files/full/"),
+        ("r5", "tests/files/full/"),
+        ("r6", "tests/files/full/"),
+    ] {
+        store.record(&RunMetadata::new(label), &collection(path)?)?;
+    }
+
+    let runs = store.runs()?;
+    let table = history::detect_changes(&runs, Metric::ExtrinsicTime, &DetectionConfig::default())?;
+
+    let expected = vec![(
+        "treasury",
+        "tip_new",
+        "r4",
+        ChangeKind::Shift,
+        140659.8333,
+        176963.0696,
+        Some(25.8092),
+        23.1136,
+    )];
+
+    assert_eq!(table.raw_list(), expected);
+
+    std::fs::remove_dir_all(&dir)?;
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+extern crate libreview;
+
+use libreview::{ExtrinsicCollection, FileScraper, LimitsConfig};
+
+use failure::Error;
+
+#[test]
+#[rustfmt::skip]
+fn test_check_limits() -> Result<(), Error> {
+    let scraper = FileScraper::new("tests/files/full/")?;
+    let mut collection = ExtrinsicCollection::new();
+
+    for result in scraper {
+        let extrinsic_result = result?.parse()?;
+        collection.push(extrinsic_result);
+    }
+
+    let config = LimitsConfig::read("tests/files/limits/limits.toml")?;
+    let mut table = collection.check_limits(&config).unwrap();
+    table.sort_by_excess();
+
+    let expected = vec![
+        ("democracy", "delegate", 2835334100, 0.1418, 0.1, true),
+        ("balances", "transfer", 185658600, 0.0093, 0.005, true),
+        ("treasury", "tip_new", 196989200, 0.0098, 0.5, false),
+        ("staking", "bond_extra", 183681200, 0.0092, 0.5, false),
+        ("identity", "add_registrar", 90736800, 0.0045, 0.5, false),
+    ];
+
+    assert_eq!(table.raw_list(), expected);
+    assert_eq!(table.offender_count(), 2);
+
+    let junit = table.to_junit();
+    assert!(junit.contains(r#"<testsuite name="check-limits" tests="5" failures="2">"#));
+    assert!(junit.contains(r#"<testcase classname="identity" name="add_registrar"/>"#));
+    assert!(junit.contains(
+        r#"<failure message="worst case weight 2835334100 takes 0.1418% of the block, ceiling is 0.1%"/>"#
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_check_limits_db_ops() -> Result<(), Error> {
+    let scraper = FileScraper::new("tests/files/db/")?;
+    let mut collection = ExtrinsicCollection::new();
+
+    for result in scraper {
+        let extrinsic_result = result?.parse()?;
+        collection.push(extrinsic_result);
+    }
+
+    // The highest step takes 201000 ns, i.e. 0.01005% of the block
+    let config: LimitsConfig = "block_weight = 2_000_000_000_000\nmax_share = 0.015".parse()?;
+    let table = collection.check_limits(&config)?;
+    assert_eq!(table.offender_count(), 0);
+
+    // Its 4 database operations add 100000000 weight
+    let config: LimitsConfig =
+        "block_weight = 2_000_000_000_000\nmax_share = 0.015\ndb_op_weight = 25_000_000".parse()?;
+    let table = collection.check_limits(&config)?;
+    assert_eq!(
+        table.raw_list(),
+        vec![("balances", "transfer", 301000000, 0.0151, 0.015, true)]
+    );
+
+    // The safety multiplier only applies to the time
+    let config: LimitsConfig =
+        "block_weight = 2_000_000_000_000\nmax_share = 0.015\nsafety_multiplier = 1.2".parse()?;
+    let table = collection.check_limits(&config)?;
+    assert_eq!(table.raw_list()[0].2, 241200000);
+
+    Ok(())
+}
@@ -0,0 +1,187 @@
+extern crate libreview;
+
+use libreview::notify::Payload;
+use libreview::{Metric, NotifyConfig};
+
+use failure::Error;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+mod common;
+use common::collection;
+
+/// Starts a webhook which answers the given statuses, one per request, and
+/// returns its URL and the received request lines and bodies.
+fn webhook(statuses: Vec<u16>) -> (String, Receiver<(String, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/hooks/bench", listener.local_addr().unwrap());
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        for status in statuses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                if let Some(value) = header.to_lowercase().strip_prefix("content-length:") {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            sender
+                .send((
+                    request_line.trim().to_string(),
+                    String::from_utf8(body).unwrap(),
+                ))
+                .unwrap();
+
+            write!(
+                reader.get_mut(),
+                "HTTP/1.1 {} Status\r\nContent-Length: 0\r\n\r\n",
+                status
+            )
+            .unwrap();
+        }
+    });
+
+    (url, receiver)
+}
+
+fn config(url: &str, outbox: &std::path::Path) -> NotifyConfig {
+    format!(
+        r#"
+        url = "{}"
+        retries = 2
+        retry_delay = 0.01
+        outbox = "{}"
+
+        [[rules]]
+        name = "regression"
+        max_increase = 5.0
+
+        [[rules]]
+        name = "significant regression"
+        max_increase = 1.0
+        significant_only = true
+        "#,
+        url,
+        outbox.display()
+    )
+    .parse()
+    .unwrap()
+}
+
+fn payload(config: &NotifyConfig) -> Result<Payload, Error> {
+    let old = collection("tests/files/shortened/")?;
+    let new = collection("tests/files/full/")?;
+    let mut table = old.compare(&new, Metric::ExtrinsicTime)?;
+    table.sort_by_change();
+
+    Ok(Payload {
+        old: "old".to_string(),
+        new: "new".to_string(),
+        old_commit: None,
+        new_commit: None,
+        hardware_factor: None,
+        regressions: config.evaluate(&table),
+    })
+}
+
+#[test]
+fn test_evaluate_rules() -> Result<(), Error> {
+    let outbox = std::env::temp_dir().join("bench-review-outbox-unused.jsonl");
+    let config = config("http://127.0.0.1:1/", &outbox);
+
+    let regressions: Vec<(String, String, f64)> = payload(&config)?
+        .regressions
+        .into_iter()
+        .map(|r| (r.rule, r.extrinsic, r.percentage))
+        .collect();
+
+    let expected = vec![
+        ("regression".to_string(), "tip_new".to_string(), 25.8092),
+        (
+            "regression".to_string(),
+            "add_registrar".to_string(),
+            7.9227,
+        ),
+        (
+            "significant regression".to_string(),
+            "tip_new".to_string(),
+            25.8092,
+        ),
+    ];
+
+    assert_eq!(regressions, expected);
+
+    Ok(())
+}
+
+#[test]
+fn test_deliver() -> Result<(), Error> {
+    let outbox =
+        std::env::temp_dir().join(format!("bench-review-outbox-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&outbox);
+
+    // Fails once, then succeeds
+    let (url, requests) = webhook(vec![500, 200]);
+    let config = config(&url, &outbox);
+    let payload = payload(&config)?;
+    config.deliver(&payload)?;
+
+    let (request_line, body) = requests.recv()?;
+    assert_eq!(request_line, "POST /hooks/bench HTTP/1.1");
+    assert_eq!(requests.recv()?.1, body);
+
+    let body: serde_json::Value = serde_json::from_str(&body)?;
+    assert_eq!(body["regressions"][0]["extrinsic"], "tip_new");
+    assert!(!outbox.exists());
+
+    Ok(())
+}
+
+#[test]
+fn test_deliver_to_outbox() -> Result<(), Error> {
+    let outbox = std::env::temp_dir().join(format!(
+        "bench-review-outbox-failed-{}.jsonl",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&outbox);
+
+    // All three attempts fail
+    let (url, requests) = webhook(vec![503, 503, 503, 200]);
+    let config = config(&url, &outbox);
+    assert!(config.deliver(&payload(&config)?).is_err());
+    assert_eq!(requests.try_iter().count(), 3);
+
+    let pending = std::fs::read_to_string(&outbox)?;
+    assert_eq!(pending.lines().count(), 1);
+
+    // The next delivery of the outbox succeeds
+    assert_eq!(config.flush_outbox()?, 1);
+    assert_eq!(requests.recv()?.1, pending.trim());
+    assert!(!outbox.exists());
+    assert_eq!(config.flush_outbox()?, 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_flush_unreadable_outbox() {
+    // A directory can not be read as outbox
+    let config = config("http://127.0.0.1:1/", &std::env::temp_dir());
+    assert!(config.flush_outbox().is_err());
+}
@@ -1,6 +1,6 @@
 extern crate libreview;
 
-use libreview::{ExtrinsicCollection, FileScraper};
+use libreview::{Baseline, ExtrinsicCollection, FileScraper, Metric};
 
 use failure::Error;
 
@@ -17,7 +17,7 @@ fn test_overview_table_full_files() -> Result<(), Error> {
         collection.push(extrinsic_result);
     }
 
-    let mut table = collection.generate_ratio_table().unwrap();
+    let mut table = collection.generate_ratio_table(&Baseline::Fastest(Metric::ExtrinsicTime)).unwrap();
     table.sort_by_ratio();
 
     let expected = [
@@ -31,16 +31,13 @@ fn test_overview_table_full_files() -> Result<(), Error> {
     let list = table.raw_list();
     assert_eq!(list.len(), 5);
 
-    let mut counter = 0;
-    for entry in list {
+    for (counter, entry) in list.into_iter().enumerate() {
         assert_eq!(entry.0, expected[counter].0);
         assert_eq!(entry.1, expected[counter].1);
         assert_eq!(entry.2, expected[counter].2);
         assert_eq!(entry.3, expected[counter].3);
         assert_eq!(entry.4, expected[counter].4);
         assert_eq!(entry.5, expected[counter].5);
-
-        counter += 1;
     }
 
     Ok(())
@@ -58,15 +55,15 @@ fn test_overview_table_shortened() -> Result<(), Error> {
         collection.push(extrinsic_result);
     }
 
-    let mut table = collection.generate_ratio_table().unwrap();
+    let mut table = collection.generate_ratio_table(&Baseline::Fastest(Metric::ExtrinsicTime)).unwrap();
     table.sort_by_ratio();
 
     let expected = [
-        ("identity", "add_registrar", 76600.8, 43874.4, 1.0, 0.0),
-        ("treasury", "tip_new", 140659.8333, 61608.3333, 1.8363, 83.6271),
-        ("balances", "transfer", 187680.2, 83780.8, 2.4501, 145.0108),
-        ("staking", "bond_extra", 188244.0, 117963.0, 2.4575, 145.7468),
-        ("democracy", "delegate", 1501419.6, 464099.8, 19.6006, 1860.0573),
+        ("identity", "add_registrar", 76600.8, 43874.4, 1.0, 0.0, 1.0, 0.0, 120475.2, 1.0),
+        ("treasury", "tip_new", 140659.8333, 61608.3333, 1.8363, 83.6271, 1.4042, 40.4198, 202268.1667, 1.6789),
+        ("balances", "transfer", 187680.2, 83780.8, 2.4501, 145.0108, 1.9096, 90.956, 271461.0, 2.2533),
+        ("staking", "bond_extra", 188244.0, 117963.0, 2.4575, 145.7468, 2.6887, 168.8652, 306207.0, 2.5417),
+        ("democracy", "delegate", 1501419.6, 464099.8, 19.6006, 1860.0573, 10.5779, 957.7918, 1965519.4, 16.3147),
     ];
 
     let list = table.raw_list();
@@ -78,16 +75,87 @@ fn test_overview_table_shortened() -> Result<(), Error> {
     }
     */
 
-    let mut counter = 0;
-    for entry in list {
+    for (counter, entry) in list.into_iter().enumerate() {
         assert_eq!(entry.0, expected[counter].0);
         assert_eq!(entry.1, expected[counter].1);
         assert_eq!(entry.2, expected[counter].2);
         assert_eq!(entry.3, expected[counter].3);
         assert_eq!(entry.4, expected[counter].4);
         assert_eq!(entry.5, expected[counter].5);
+        assert_eq!(entry.6, expected[counter].6);
+        assert_eq!(entry.7, expected[counter].7);
+        assert_eq!(entry.8, expected[counter].8);
+        assert_eq!(entry.9, expected[counter].9);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[rustfmt::skip]
+/// Ratios are relative to the reference extrinsic instead of the fastest one.
+fn test_overview_table_reference_baseline() -> Result<(), Error> {
+    let scraper = FileScraper::new("tests/files/shortened/")?;
+    let mut collection = ExtrinsicCollection::new();
+
+    for result in scraper {
+        let extrinsic_result = result?.parse()?;
+        collection.push(extrinsic_result);
+    }
+
+    let mut table = collection
+        .generate_ratio_table(&Baseline::Reference("balances.transfer".parse()?))
+        .unwrap();
+    table.sort_by_ratio();
+
+    let expected = [
+        ("identity", "add_registrar", 76600.8, 43874.4, 0.4081, -59.1855, 0.5237, -47.6319, 120475.2, 0.4438, 76600.8),
+        ("treasury", "tip_new", 140659.8333, 61608.3333, 0.7495, -25.0535, 0.7354, -26.4649, 202268.1667, 0.7451, 140659.8333),
+        ("balances", "transfer", 187680.2, 83780.8, 1.0, 0.0, 1.0, 0.0, 271461.0, 1.0, 197333.5),
+        ("staking", "bond_extra", 188244.0, 117963.0, 1.003, 0.3004, 1.408, 40.7996, 306207.0, 1.128, 188244.0),
+        ("democracy", "delegate", 1501419.6, 464099.8, 7.9999, 699.9883, 5.5395, 453.9453, 1965519.4, 7.2405, 1596785.3333),
+    ];
+
+    assert_eq!(table.raw_list(), expected);
+
+    // The reference must be part of the results.
+    let err = collection
+        .generate_ratio_table(&Baseline::Reference("system.remark".parse()?))
+        .unwrap_err();
+    assert_eq!(err.to_string(), "reference extrinsic system.remark is not part of the results");
+
+    Ok(())
+}
+
+#[test]
+#[rustfmt::skip]
+/// Extrinsics ranked by the extrinsic time of the step with the highest
+/// component values instead of the average.
+fn test_overview_table_worst_case() -> Result<(), Error> {
+    let scraper = FileScraper::new("tests/files/full/")?;
+    let mut collection = ExtrinsicCollection::new();
+
+    for result in scraper {
+        let extrinsic_result = result?.parse()?;
+        collection.push(extrinsic_result);
+    }
+
+    let mut table = collection.generate_ratio_table(&Baseline::default()).unwrap();
+    table.sort_by_worst_case();
+
+    let expected = [
+        ("identity", "add_registrar", 82669.6368, 90736.8),
+        ("staking", "bond_extra", 186282.0091, 183681.2),
+        ("balances", "transfer", 184602.4227, 185658.6),
+        ("treasury", "tip_new", 176963.0696, 196989.2),
+        ("democracy", "delegate", 1514997.45, 2835334.1),
+    ];
+
+    let list = table.raw_list();
+    assert_eq!(list.len(), expected.len());
 
-        counter += 1;
+    for (entry, expected) in list.into_iter().zip(expected.iter()) {
+        assert_eq!((entry.0, entry.1, entry.2, entry.10), *expected);
     }
 
     Ok(())
@@ -0,0 +1,53 @@
+extern crate libreview;
+
+use libreview::Metric;
+
+use failure::Error;
+
+mod common;
+use common::collection;
+
+#[test]
+#[rustfmt::skip]
+fn test_rank_stability() -> Result<(), Error> {
+    let old = collection("tests/files/shortened/")?;
+    let new = collection("tests/files/full/")?;
+
+    // `treasury.tip_new` and `balances.transfer` swap their ranks
+    let mut table = old.compare_ranks(&new, Metric::StorageRootTime)?;
+    table.sort_by_movement();
+
+    assert_eq!(table.kendall_tau(), Some(0.8));
+    assert_eq!(table.spearman(), Some(0.9));
+
+    let expected = vec![
+        ("treasury", "tip_new", 2.0, 3.0, 1.0, 1.4042, 1.873),
+        ("balances", "transfer", 3.0, 2.0, -1.0, 1.9096, 1.7632),
+        ("identity", "add_registrar", 1.0, 1.0, 0.0, 1.0, 1.0),
+        ("staking", "bond_extra", 4.0, 4.0, 0.0, 2.6887, 2.5769),
+        ("democracy", "delegate", 5.0, 5.0, 0.0, 10.5779, 9.8878),
+    ];
+
+    assert_eq!(table.raw_list(), expected);
+
+    table.retain_moved(1);
+    assert_eq!(table.raw_list(), expected[..1].to_vec());
+
+    Ok(())
+}
+
+#[test]
+fn test_rank_stability_unchanged() -> Result<(), Error> {
+    let old = collection("tests/files/shortened/")?;
+    let new = collection("tests/files/full/")?;
+
+    let mut table = old.compare_ranks(&new, Metric::ExtrinsicTime)?;
+    assert_eq!(table.len(), 5);
+    assert_eq!(table.kendall_tau(), Some(1.0));
+    assert_eq!(table.spearman(), Some(1.0));
+
+    table.retain_moved(10);
+    assert!(table.is_empty());
+
+    Ok(())
+}
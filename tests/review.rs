@@ -0,0 +1,106 @@
+extern crate libreview;
+
+use libreview::tables::ReviewStatus;
+use libreview::ReviewLedger;
+
+use failure::Error;
+
+mod common;
+use common::collection;
+
+#[test]
+fn test_review_ledger() -> Result<(), Error> {
+    let path = std::env::temp_dir().join(format!("bench-reviews-{}.json", std::process::id()));
+
+    let shortened = collection("tests/files/shortened/")?;
+    let ids = vec![
+        "balances.transfer".parse()?,
+        "identity.add_registrar".parse()?,
+    ];
+
+    let mut ledger = ReviewLedger::open(&path)?;
+    assert_eq!(
+        ledger.sign_off(&shortened, "alice", &ids, 1_614_607_500)?,
+        2
+    );
+    ledger.write(&path)?;
+    let ledger = ReviewLedger::open(&path)?;
+    std::fs::remove_file(&path)?;
+
+    let approval = ledger.approval("balances", "transfer").unwrap();
+    assert_eq!(approval.reviewer, "alice");
+    assert_eq!(approval.average, 187680.2);
+    assert_eq!(approval.ratio, 2.4501);
+
+    // The same files are approved
+    let mut table = ledger.review(&shortened)?;
+    table.sort_by_extrinsic();
+    assert_eq!(
+        table.raw_list(),
+        vec![
+            (
+                "balances",
+                "transfer",
+                ReviewStatus::Approved,
+                Some("alice"),
+                Some(187680.2),
+                187680.2,
+                Some(0.0),
+                Some(2.4501),
+                2.4501
+            ),
+            (
+                "democracy",
+                "delegate",
+                ReviewStatus::NeverReviewed,
+                None,
+                None,
+                1501419.6,
+                None,
+                None,
+                19.6006
+            ),
+            (
+                "identity",
+                "add_registrar",
+                ReviewStatus::Approved,
+                Some("alice"),
+                Some(76600.8),
+                76600.8,
+                Some(0.0),
+                Some(1.0),
+                1.0
+            ),
+            (
+                "staking",
+                "bond_extra",
+                ReviewStatus::NeverReviewed,
+                None,
+                None,
+                188244.0,
+                None,
+                None,
+                2.4575
+            ),
+            (
+                "treasury",
+                "tip_new",
+                ReviewStatus::NeverReviewed,
+                None,
+                None,
+                140659.8333,
+                None,
+                None,
+                1.8363
+            ),
+        ]
+    );
+
+    // Other result files of the approved extrinsics changed since the approval
+    let full = collection("tests/files/full/")?;
+    let table = ledger.review(&full)?;
+    assert_eq!(table.count(ReviewStatus::Changed), 2);
+    assert_eq!(table.count(ReviewStatus::Approved), 0);
+
+    Ok(())
+}
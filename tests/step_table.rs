@@ -1,6 +1,6 @@
 extern crate libreview;
 
-use libreview::{ExtrinsicCollection, FileScraper};
+use libreview::{ExtrinsicCollection, FileScraper, Metric, StepBaseline};
 
 use failure::Error;
 
@@ -15,16 +15,16 @@ fn test_step_table() -> Result<(), Error> {
         collection.push(extrinsic_result);
     }
 
-    let mut table = collection.generate_step_table().unwrap();
+    let mut table = collection.generate_step_table(&StepBaseline::Fastest(Metric::ExtrinsicTime)).unwrap();
     table.sort_by_extrinsic_incr_percentage();
 
     let expected = [
-        ("balances", "set_balance_killing", &vec![1, 1000], 122488.6667, 79915.0, 1.1565, 15.6470, 27.0004),
-        ("balances", "set_balance_killing", &vec![199, 1000], 108735.6667, 71866.3333, 1.0266, 2.6622, 14.2095),
-        ("balances", "set_balance_killing", &vec![496, 1000], 105916.0, 62925.0, 1.0, 0.0, 0.0),
-        ("democracy", "propose", &vec![19], 135927.0, 106922.0, 1.0796, 7.9605, 27.1982),
-        ("democracy", "propose", &vec![10], 130751.0, 89704.8, 1.0385, 3.8495, 6.7160),
-        ("democracy", "propose", &vec![1], 125904.3333, 84059.3333, 1.0, 0.0, 0.0),
+        ("balances", "set_balance_killing", &vec![1, 1000], 122488.6667, 79915.0, 1.1565, 15.6470, 27.0004, 1.27, 202403.6667, 1.1988),
+        ("balances", "set_balance_killing", &vec![199, 1000], 108735.6667, 71866.3333, 1.0266, 2.6622, 14.2095, 1.1421, 180602.0, 1.0697),
+        ("balances", "set_balance_killing", &vec![496, 1000], 105916.0, 62925.0, 1.0, 0.0, 0.0, 1.0, 168841.0, 1.0),
+        ("democracy", "propose", &vec![19], 135927.0, 106922.0, 1.0796, 7.9605, 27.1982, 1.272, 242849.0, 1.1566),
+        ("democracy", "propose", &vec![10], 130751.0, 89704.8, 1.0385, 3.8495, 6.7160, 1.0672, 220455.8, 1.05),
+        ("democracy", "propose", &vec![1], 125904.3333, 84059.3333, 1.0, 0.0, 0.0, 1.0, 209963.6667, 1.0),
     ];
 
     let list = table.raw_list();
@@ -35,8 +35,50 @@ fn test_step_table() -> Result<(), Error> {
     }
     */
 
-    let mut counter = 0;
-    for entry in list {
+    for (counter, entry) in list.into_iter().enumerate() {
+        assert_eq!(entry.0, expected[counter].0);
+        assert_eq!(entry.1, expected[counter].1);
+        assert_eq!(entry.2, expected[counter].2.as_slice());
+        assert_eq!(entry.3, expected[counter].3);
+        assert_eq!(entry.4, expected[counter].4);
+        assert_eq!(entry.5, expected[counter].5);
+        assert_eq!(entry.6, expected[counter].6);
+        assert_eq!(entry.7, expected[counter].7);
+        assert_eq!(entry.8, expected[counter].8);
+        assert_eq!(entry.9, expected[counter].9);
+        assert_eq!(entry.10, expected[counter].10);
+    }
+
+    Ok(())
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_step_table_lowest_components_baseline() -> Result<(), Error> {
+    let scraper = FileScraper::new("tests/files/steps/")?;
+    let mut collection = ExtrinsicCollection::new();
+
+    for result in scraper {
+        let extrinsic_result = result?.parse()?;
+        collection.push(extrinsic_result);
+    }
+
+    let mut table = collection.generate_step_table(&StepBaseline::LowestComponents).unwrap();
+    table.sort_by_extrinsic_incr_percentage();
+
+    let expected = [
+        ("balances", "set_balance_killing", &vec![1, 1000], 122488.6667, 79915.0, 1.0, 0.0, 0.0),
+        ("balances", "set_balance_killing", &vec![199, 1000], 108735.6667, 71866.3333, 0.8877, -11.228, -10.0715),
+        ("balances", "set_balance_killing", &vec![496, 1000], 105916.0, 62925.0, 0.8647, -13.53, -21.2601),
+        ("democracy", "propose", &vec![19], 135927.0, 106922.0, 1.0796, 7.9605, 27.1982),
+        ("democracy", "propose", &vec![10], 130751.0, 89704.8, 1.0385, 3.8495, 6.716),
+        ("democracy", "propose", &vec![1], 125904.3333, 84059.3333, 1.0, 0.0, 0.0),
+    ];
+
+    let list = table.raw_list();
+    assert_eq!(list.len(), expected.len());
+
+    for (counter, entry) in list.into_iter().enumerate() {
         assert_eq!(entry.0, expected[counter].0);
         assert_eq!(entry.1, expected[counter].1);
         assert_eq!(entry.2, expected[counter].2.as_slice());
@@ -45,9 +87,63 @@ fn test_step_table() -> Result<(), Error> {
         assert_eq!(entry.5, expected[counter].5);
         assert_eq!(entry.6, expected[counter].6);
         assert_eq!(entry.7, expected[counter].7);
+    }
+
+    let baselines = table.baselines();
+    assert_eq!(baselines[0], ("balances", "set_balance_killing", [1, 1000].as_ref()));
+    assert_eq!(baselines[1], ("democracy", "propose", [1].as_ref()));
+
+    Ok(())
+}
+
+#[test]
+fn test_step_table_component_baseline() -> Result<(), Error> {
+    let scraper = FileScraper::new("tests/files/steps/")?;
+    let mut collection = ExtrinsicCollection::new();
+
+    for result in scraper {
+        let extrinsic_result = result?.parse()?;
+        collection.push(extrinsic_result);
+    }
+
+    // `democracy.propose` has no `e` component and falls back to the lowest values.
+    let mut table = collection
+        .generate_step_table(&StepBaseline::Component("e".to_string()))
+        .unwrap();
+    table.sort_by_extrinsic_incr_percentage();
+
+    let baselines = table.baselines();
+    assert_eq!(
+        baselines[0],
+        ("balances", "set_balance_killing", [1, 1000].as_ref())
+    );
+    assert_eq!(baselines[1], ("democracy", "propose", [1].as_ref()));
 
-        counter += 1;
+    Ok(())
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_component_table() -> Result<(), Error> {
+    let scraper = FileScraper::new("tests/files/steps/")?;
+    let mut collection = ExtrinsicCollection::new();
+
+    for result in scraper {
+        let extrinsic_result = result?.parse()?;
+        collection.push(extrinsic_result);
     }
 
+    let mut table = collection.generate_component_table().unwrap();
+    table.sort_by_component();
+
+    // `set_balance_killing` never varies `e`, so there is no group for it.
+    let expected = [
+        ("balances", "set_balance_killing", "u", vec![1, 199, 496], -31.5865, -33.9903),
+        ("democracy", "propose", "p", vec![1, 10, 19], 556.8148, 1270.1481),
+    ];
+
+    let list = table.raw_list();
+    assert_eq!(list, expected);
+
     Ok(())
 }
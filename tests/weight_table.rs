@@ -0,0 +1,140 @@
+extern crate libreview;
+
+use libreview::tables::{ComponentFinding, WeightStatus};
+use libreview::{codegen, weightfile, WeightConfig};
+
+use failure::Error;
+
+mod common;
+use common::collection;
+
+#[test]
+#[rustfmt::skip]
+fn test_weight_table() -> Result<(), Error> {
+    let collection = collection("tests/files/steps/")?;
+
+    let mut table = collection.generate_weight_table(&WeightConfig::default()).unwrap();
+    table.sort_by_extrinsic();
+
+    // `set_balance_killing` gets slightly faster with higher `u` values, so the
+    // negative slope is treated as zero.
+    let expected = vec![
+        ("balances", "set_balance_killing", 119708175, vec![("u", 0), ("e", 0)]),
+        ("democracy", "propose", 125292630, vec![("p", 556815)]),
+    ];
+
+    assert_eq!(table.raw_list(), expected);
+
+    Ok(())
+}
+
+#[test]
+#[rustfmt::skip]
+/// Newer result files contain the database operations.
+fn test_weight_table_db_ops() -> Result<(), Error> {
+    let collection = collection("tests/files/db/")?;
+
+    let config = WeightConfig {
+        db_op_weight: 1_000_000.0,
+        ..Default::default()
+    };
+    let table = collection.generate_weight_table(&config).unwrap();
+    assert_eq!(table.raw_list(), vec![("balances", "transfer", 104159979, vec![("u", 1010067)])]);
+
+    // Safety margin of 20% on the measured times
+    let config = WeightConfig {
+        safety_multiplier: 1.2,
+        ..Default::default()
+    };
+    let table = collection.generate_weight_table(&config).unwrap();
+    assert_eq!(table.raw_list(), vec![("balances", "transfer", 120191974, vec![("u", 1212080)])]);
+
+    Ok(())
+}
+
+#[test]
+fn test_generate_weights() -> Result<(), Error> {
+    let collection = collection("tests/files/steps/")?;
+
+    let mut table = collection.generate_weight_table(&WeightConfig::default())?;
+    table.sort_by_extrinsic();
+
+    let module = codegen::render_weights(&table, codegen::DEFAULT_TEMPLATE)?;
+    assert!(module.contains(
+        "pub mod democracy {\n    use super::Weight;\n\n    pub fn propose(p: u32) -> Weight {\n        \
+         (125292630 as Weight)\n            \
+         .saturating_add((p as Weight).saturating_mul(556815))\n    }\n}\n"
+    ));
+    // Components without weight are neither rendered nor used
+    assert!(module.contains(
+        "pub fn set_balance_killing(_u: u32, _e: u32) -> Weight {\n        \
+         (119708175 as Weight)\n    }\n"
+    ));
+
+    // Custom template
+    let template = "{{#pallets}}{{#functions}}{{pallet}}::{{extrinsic}}: {{base_weight}}\
+                    {{#components}} + {{weight}} * {{component}}{{/components}}\n\
+                    {{/functions}}{{/pallets}}";
+    let module = codegen::render_weights(&table, template)?;
+    assert_eq!(
+        module,
+        "balances::set_balance_killing: 119708175\n\
+         democracy::propose: 125292630 + 556815 * p\n"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[rustfmt::skip]
+fn test_compare_weights() -> Result<(), Error> {
+    let collection = collection("tests/files/full/")?;
+    let functions = weightfile::read_weight_file("tests/files/weights/weights.rs")?;
+
+    let mut table = collection
+        .compare_weights(&functions, None, &WeightConfig::default(), 10.0)
+        .unwrap();
+    table.sort_by_percentage();
+
+    // `staking.bond_extra` has no weight function and is skipped.
+    let expected = vec![
+        ("balances", "transfer", 150000000, 187671484, -20.0731, WeightStatus::UnderWeighted),
+        ("democracy", "delegate", 2721000000, 2783095388, -2.2312, WeightStatus::Ok),
+        ("treasury", "tip_new", 199536000, 203911298, -2.1457, WeightStatus::Ok),
+        ("identity", "add_registrar", 107100000, 90536407, 18.295, WeightStatus::OverWeighted),
+    ];
+
+    assert_eq!(table.raw_list(), expected);
+
+    table.retain_deviations();
+    assert_eq!(table.raw_list().len(), 2);
+
+    Ok(())
+}
+
+#[test]
+fn test_check_components() -> Result<(), Error> {
+    let collection = collection("tests/files/full/")?;
+    let functions = weightfile::read_weight_file("tests/files/weights/outdated.rs")?;
+
+    let mut table = collection.check_components(&functions, None).unwrap();
+    table.sort_by_extrinsic();
+
+    let renamed = ComponentFinding::Renamed {
+        from: "x".to_string(),
+        to: "e".to_string(),
+    };
+    let missing = ComponentFinding::Missing("r".to_string());
+    let extra = ComponentFinding::Extra("s".to_string());
+
+    let expected = vec![
+        ("balances", "transfer", &renamed),
+        ("democracy", "delegate", &missing),
+        ("identity", "add_registrar", &extra),
+        ("staking", "bond_extra", &ComponentFinding::MissingFunction),
+    ];
+
+    assert_eq!(table.raw_list(), expected);
+
+    Ok(())
+}